@@ -0,0 +1,61 @@
+//! Command-line argument parsing for `LoadedImage::load_options`
+//!
+//! When launched from the UEFI Shell, arguments should be obtained from the `ShellParameters` protocol
+//! (which has already split them). Otherwise (e.g. started from a boot option) the command line is a single
+//! UTF-16 string in the load options buffer, which `parse_load_options` splits into arguments.
+use super::Str16;
+
+/// Split a load options buffer into arguments
+///
+/// Rules (a subset of the UEFI Shell's):
+/// - Arguments are separated by runs of spaces or tabs
+/// - An argument starting with `"` extends to the next `"` (or the end of the buffer), and may contain
+///   whitespace. The quotes are not included in the argument.
+/// - Parsing stops at the first NUL (the buffer is usually, but not always, NUL terminated)
+///
+/// An empty buffer (no load options) produces no arguments, as does a buffer that isn't 2-byte aligned. A
+/// trailing odd byte is ignored. Note that whether the first argument is the image name depends on how the
+/// image was started (the shell includes it, boot options usually don't).
+pub fn parse_load_options(opts: &[u8]) -> ArgIter {
+	let units = if opts.as_ptr() as usize % 2 != 0 {
+			&[][..]
+		}
+		else {
+			// SAFE: Aligned, and the length is rounded down to whole code units
+			unsafe { ::core::slice::from_raw_parts(opts.as_ptr() as *const u16, opts.len() / 2) }
+		};
+	let len = units.iter().position(|&c| c == 0).unwrap_or(units.len());
+	ArgIter(&units[..len])
+}
+
+/// Iterator over arguments in a load options buffer
+pub struct ArgIter<'a>(&'a [u16]);
+impl<'a> Iterator for ArgIter<'a>
+{
+	type Item = &'a Str16;
+	fn next(&mut self) -> Option<&'a Str16> {
+		fn is_space(c: u16) -> bool {
+			c == b' ' as u16 || c == b'\t' as u16
+		}
+		let start = match self.0.iter().position(|&c| !is_space(c))
+			{
+			Some(v) => v,
+			None => { self.0 = &[]; return None; },
+			};
+		let s = &self.0[start..];
+		let (arg, rest) = if s[0] == b'"' as u16 {
+				let s = &s[1..];
+				match s.iter().position(|&c| c == b'"' as u16)
+				{
+				Some(end) => (&s[..end], &s[end+1..]),
+				None => (s, &[][..]),
+				}
+			}
+			else {
+				let end = s.iter().position(|&c| is_space(c)).unwrap_or(s.len());
+				(&s[..end], &s[end..])
+			};
+		self.0 = rest;
+		Some( Str16::from_slice(arg) )
+	}
+}
@@ -8,10 +8,42 @@ use super::{Void,Status,Guid,Handle};
 use super::{PhysicalAddress,VirtualAddress};
 
 pub mod protocols;
+mod ticker;
+mod owned_event;
+
+pub use self::ticker::Ticker;
+pub use self::owned_event::OwnedEvent;
 
 /// Task Priority Level
 pub type Tpl = usize;
 
+pub const TPL_APPLICATION: Tpl = 4;
+pub const TPL_CALLBACK: Tpl = 8;
+pub const TPL_NOTIFY: Tpl = 16;
+pub const TPL_HIGH_LEVEL: Tpl = 31;
+
+// Event types (for `create_event`)
+pub const EVT_TIMER: u32 = 0x8000_0000;
+pub const EVT_RUNTIME: u32 = 0x4000_0000;
+pub const EVT_NOTIFY_WAIT: u32 = 0x0000_0100;
+pub const EVT_NOTIFY_SIGNAL: u32 = 0x0000_0200;
+pub const EVT_SIGNAL_EXIT_BOOT_SERVICES: u32 = 0x0000_0201;
+pub const EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE: u32 = 0x6000_0202;
+
+/// Number of times `BootServices::exit_boot_services_loop` tries to exit before giving up
+pub const EXIT_BOOT_SERVICES_ATTEMPTS: usize = 3;
+
+// Attributes for `BootServices::open_protocol`
+pub const OPEN_PROTOCOL_BY_HANDLE_PROTOCOL: u32 = 0x01;
+pub const OPEN_PROTOCOL_GET_PROTOCOL: u32 = 0x02;
+pub const OPEN_PROTOCOL_TEST_PROTOCOL: u32 = 0x04;
+pub const OPEN_PROTOCOL_BY_CHILD_CONTROLLER: u32 = 0x08;
+pub const OPEN_PROTOCOL_BY_DRIVER: u32 = 0x10;
+pub const OPEN_PROTOCOL_EXCLUSIVE: u32 = 0x20;
+
+/// Maximum number of GUID/interface pairs accepted by `BootServices::install_multiple`
+pub const INSTALL_MULTIPLE_MAX_PAIRS: usize = 6;
+
 /// Raw type aliases
 pub mod raw
 {
@@ -22,8 +54,9 @@ pub mod raw
 //#[repr(C)]
 pub type PoolPointer<T> = *mut T;
 
-/// Wrapped `Event` handle
+/// Wrapped `Event` handle (same ABI as `raw::Event`, so it can be passed to and from the firmware directly)
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
 pub struct Event(pub raw::Event);
 
 #[repr(C)]
@@ -33,7 +66,7 @@ pub struct BootServices
 	
 	// Task Priority
 	pub raise_tpl: efi_fcn! { fn(Tpl) -> Tpl },
-	pub restore_tpl: efi_fcn! { fn(Tpl) -> Tpl },
+	pub restore_tpl: efi_fcn! { fn(Tpl) -> () },
 
 	// Memory
 	pub allocate_pages: efi_fcn!{ fn(AllocateType, MemoryType, /*no_pages:*/ usize, &mut PhysicalAddress) -> Status },
@@ -55,6 +88,7 @@ pub struct BootServices
 	pub reinstall_protocol_interface: efi_fcn!{ fn(Handle, &Guid, /*old:*/ *mut Void, /*new:*/ *mut Void) -> Status },
 	pub uninstall_protocol_interface: efi_fcn!{ fn(Handle, &Guid, *mut Void) -> Status },
 	pub handle_protocol: efi_fcn!{ fn(Handle, &Guid, &mut *mut Void) -> Status },
+	/// Reserved slot (unused)
 	pub pc_handle_protocol: efi_fcn!{ fn(Handle, &Guid, &mut *mut Void) -> Status },
 	pub register_protocol_notify: efi_fcn!{ fn(&Guid, Event, &mut *mut Void) -> Status },
 	pub locate_handle: efi_fcn!{ fn(LocateSearchType, Option<&Guid>, *mut Void, &mut usize, *mut Handle) -> Status },
@@ -69,33 +103,61 @@ pub struct BootServices
 	pub exit_boot_services: efi_fcn!{ fn(Handle, /*map_key:*/ usize) -> Status },
 	
 	// Misc functions
-	pub get_next_monotonic_count: efi_fcn!{ fn() -> Status },
-	pub stall: efi_fcn!{ fn() -> Status },
-	pub set_watchdog_timer: efi_fcn!{ fn() -> Status },
+	pub get_next_monotonic_count: efi_fcn!{ fn(&mut u64) -> Status },
+	pub stall: efi_fcn!{ fn(/*microseconds:*/ usize) -> Status },
+	pub set_watchdog_timer: efi_fcn!{ fn(/*timeout:*/ usize, /*watchdog_code:*/ u64, /*data_size:*/ usize, /*watchdog_data:*/ *const u16) -> Status },
 
 	// DriverSupport Services
-	pub connect_controller: efi_fcn!{ fn() -> Status },
-	pub disconnect_controller: efi_fcn!{ fn() -> Status },
+	pub connect_controller: efi_fcn!{ fn(Handle, /*driver_image_handles:*/ *const Handle, /*remaining_device_path:*/ Option<&DevicePath>, /*recursive:*/ bool) -> Status },
+	pub disconnect_controller: efi_fcn!{ fn(Handle, /*driver_image_handle:*/ Handle, /*child_handle:*/ Handle) -> Status },
 
 	// Open/Close Protocol Services
 	pub open_protocol: efi_fcn!{ fn(Handle, &Guid, Option<&mut *mut Void>, Handle, Handle, u32) -> Status },
 	pub close_protocol: efi_fcn!{ fn(Handle, &Guid, Handle, Handle) -> Status },
-	pub open_protocol_information: efi_fcn!{ fn() -> Status },
+	pub open_protocol_information: efi_fcn!{ fn(Handle, &Guid, &mut PoolPointer<OpenProtocolInformationEntry>, &mut usize) -> Status },
 
 	// Library Services
 	pub protocols_per_handle: efi_fcn!{ fn(Handle, &mut PoolPointer<&Guid>, &mut usize) -> Status },
 	pub locate_handle_buffer: efi_fcn!{ fn(LocateSearchType, Option<&Guid>, *const Void, &mut usize, &mut *mut Handle) -> Status },
 	pub locate_protocol: efi_fcn!{ fn(protocol: *const Guid, registration: *mut Void, interface: *mut *mut Void) -> Status },
+	// NOTE: These two are C-variadic, so can't be called through these (non-variadic) signatures (see `install_multiple`)
 	pub install_multiple_protocol_interfaces: efi_fcn!{ fn() -> Status },
 	pub uninstall_multiple_protocol_interfaces: efi_fcn!{ fn() -> Status },
 
-	// CRC
-	pub calculate_crc32: efi_fcn!{ fn() -> Status },
+	// CRC (EFI 1.10+, may be null on older firmware)
+	pub calculate_crc32: Option<efi_fcn!{ fn(*const Void, usize, &mut u32) -> Status }>,
 
 	// Misc Services
-	pub copy_mem: efi_fcn!{ fn() -> Status },
-	pub set_mem: efi_fcn!{ fn() -> Status },
-	pub create_event_ex: efi_fcn!{ fn(u32, /*notify_tpl:*/ Tpl, /*notify_function:*/ Option<EventNotifyFcn>, *mut Void, &Guid, &mut raw::Event) -> Status },
+	pub copy_mem: efi_fcn!{ fn(/*destination:*/ *mut Void, /*source:*/ *const Void, /*length:*/ usize) -> () },
+	pub set_mem: efi_fcn!{ fn(/*buffer:*/ *mut Void, /*size:*/ usize, /*value:*/ u8) -> () },
+	// - UEFI 2.0+, may be null on older firmware
+	pub create_event_ex: Option<efi_fcn!{ fn(u32, /*notify_tpl:*/ Tpl, /*notify_function:*/ Option<EventNotifyFcn>, *mut Void, &Guid, &mut raw::Event) -> Status }>,
+}
+// The table is 44 function pointers after the 24-byte header (as of UEFI 2.0, unchanged since), a missing or
+// extra field would shift every later service. This fails to compile if the size is wrong.
+#[cfg(target_pointer_width="64")]
+#[allow(dead_code)]
+const BOOT_SERVICES_SIZE_CHECK: [(); 0x178] = [(); mem::size_of::<BootServices>()];
+
+impl BootServices
+{
+	/// Revision of the boot services table (`major << 16 | minor`)
+	#[inline]
+	pub fn revision(&self) -> u32 {
+		self.hdr.revision
+	}
+
+	/// Returns `UNSUPPORTED` if the table is older than the requested revision
+	///
+	/// Used to avoid calling through fields that don't exist (or are null) on older firmware
+	fn require_revision(&self, min: u32) -> Result<(), Status> {
+		if self.revision() >= min {
+			Ok( () )
+		}
+		else {
+			Err(::status::UNSUPPORTED)
+		}
+	}
 }
 
 /// Event, Timer, and Task Priority Services
@@ -115,7 +177,38 @@ impl BootServices
 			.err_or(Event(rv))
 	}
 
+	/// Create an event with a typed notify function and context
+	///
+	/// `notify` is called at `notify_tpl` (`TPL_CALLBACK` or `TPL_NOTIFY`) when the event is signalled (for
+	/// `EVT_NOTIFY_SIGNAL`) or checked/waited on (for `EVT_NOTIFY_WAIT`). Notify functions can't block (no
+	/// `wait_for_event`, which needs `TPL_APPLICATION`), and at `TPL_NOTIFY` should only use the memory,
+	/// event, and timer services. They can interrupt the main code at any point, so the context must be
+	/// safe to access concurrently.
+	pub fn create_event_with_notify<T: Sync>(&self, ty: u32, notify_tpl: Tpl, notify: extern "win64" fn(Event, &'static T), context: &'static T) -> Result<Event, Status>
+	{
+		// SAFE: `&T` and `*mut Void` have the same ABI, and calling an `extern fn` through an unsafe fn pointer is fine
+		let nf: EventNotifyFcn = unsafe { mem::transmute(notify) };
+		self.create_event(ty, notify_tpl, Some( (nf, context as *const T as *mut Void) ))
+	}
+
+	/// Create an event that calls a Rust closure when notified
+	///
+	/// The closure is moved into a pool allocation (there's no global allocator), which is freed when the
+	/// returned `OwnedEvent` is dropped. The same restrictions as `create_event_with_notify` apply to what it
+	/// can do. It must not panic: it's called from the firmware, and unwinding across that boundary is undefined.
+	///
+	/// The closure must be `'static` (like `create_event_with_notify`'s context) because the `OwnedEvent` can
+	/// be leaked with `mem::forget`, leaving the event live with nothing to close it.
+	pub fn create_event_closure<'a, F>(&'a self, ty: u32, notify_tpl: Tpl, f: F) -> Result<OwnedEvent<'a>, Status>
+	where
+		F: FnMut() + Send + 'static
+	{
+		OwnedEvent::new(self, ty, notify_tpl, f)
+	}
+
 	/// Create a new signalable event attached to a group
+	///
+	/// Returns `UNSUPPORTED` on pre-2.0 firmware
 	pub fn create_event_for_group(&self, ty: u32, notify_tpl: Tpl, notify_fcn: Option<(EventNotifyFcn,*mut Void)>, group: Guid) -> Result<Event, Status>
 	{
 		let (nf, nc) = match notify_fcn
@@ -123,9 +216,15 @@ impl BootServices
 			Some(v) => (Some(v.0), v.1),
 			None => (None, ::core::ptr::null_mut()),
 			};
+		self.require_revision(::REVISION_2_00)?;
+		let create_event_ex = match self.create_event_ex
+			{
+			Some(f) => f,
+			None => return Err(::status::UNSUPPORTED),
+			};
 		let mut rv = 0 as raw::Event;	 // `Event` is a pointer
 		// SAFE: Passed function pointer is inherently 'static, and the pointer isn't dereferenced by the environment
-		(unsafe { (self.create_event_ex)(ty, notify_tpl, nf, nc, &group, &mut rv) })
+		(unsafe { create_event_ex(ty, notify_tpl, nf, nc, &group, &mut rv) })
 			.err_or(Event(rv))
 	}
 
@@ -136,9 +235,10 @@ impl BootServices
 	}
 
 	/// Signal an event (signals entire group if the event is part of a group)
-	pub fn signal_event(&self, ev: Event) -> Status {
+	pub fn signal_event(&self, ev: Event) -> Result<(), Status> {
 		// SAFE: No memory unsafety because the wrapped handle can only have come from a successful `create_event*`
 		(unsafe { (self.signal_event)(ev.0) })
+			.err_or( () )
 	}
 
 	/// Wait for an event to be signaled, returns the index of the signalled event
@@ -153,8 +253,38 @@ impl BootServices
 			.err_or(rv)
 	}
 
-	/// Check if an event has been signaled
+	/// Wait for a single event to be signaled
+	///
+	/// Blocks until the event fires (must be called at `TPL_APPLICATION`, and the event can't be a
+	/// notify-signal event)
+	pub fn wait_for_single_event(&self, event: Event) -> Result<(), Status> {
+		self.wait_for_event(&[event]).map(|_| ())
+	}
+
+	/// Wait for an event to be signaled, giving up after `timeout_ms` milliseconds
+	///
+	/// Returns `Ok(true)` if `event` fired, or `Ok(false)` on timeout (e.g. for a "press a key within 5
+	/// seconds" prompt, pass `con_in.wait_for_key`). The timeout is a one-shot relative timer, in the
+	/// firmware's 100ns units (`timeout_ms * 10_000`), which is closed before returning.
+	///
+	/// If `event` is already signaled, this returns `Ok(true)` straight away, even with a zero timeout
+	/// (`event` is checked before the timer). As with `wait_for_event`, a successful wait clears the
+	/// signaled state.
+	pub fn wait_for_event_timeout(&self, event: Event, timeout_ms: u64) -> Result<bool, Status> {
+		let timer = self.create_event(EVT_TIMER, TPL_CALLBACK, None)?;
+		let rv = self.set_timer(&timer, TimerDelay::Relative, timeout_ms.saturating_mul(10_000))
+			.and_then(|_| self.wait_for_event(&[event, timer]));
+		self.close_event(timer);
+		Ok(rv? == 0)
+	}
+
+	/// Check if an event has been signaled, without blocking
+	///
+	/// Returns `Ok(false)` if the event isn't signaled yet (`NOT_READY`). NOTE: A successful check clears
+	/// the signaled state (e.g. a periodic timer must fire again before the next check returns true), and
+	/// `EVT_NOTIFY_SIGNAL` events can't be checked (`INVALID_PARAMETER`).
 	pub fn check_event(&self, ev: &Event) -> Result<bool,Status> {
+		// SAFE: No memory unsafety because the wrapped handle can only have come from a successful `create_event*`
 		match unsafe { (self.check_event)(ev.0) }
 		{
 		::status::SUCCESS => Ok(true),
@@ -183,7 +313,19 @@ impl BootServices
 			.err_or_else(|| unsafe { PoolVec::from_ptr(self, ptr as *mut T, capacity, 0) }) 
 	}
 
-	
+	/// Allocate a single `T` from the pool (as `BootServicesData`), initialised to `T::default()`
+	///
+	/// The allocation is freed when the returned box is dropped, which has to happen before `exit_boot_services`.
+	pub fn allocate_pool_typed<'a, T: Default>(&'a self) -> Result<PoolBox<'a, T>, Status> {
+		PoolBox::new(self, T::default())
+	}
+	/// Allocate a single zero-filled `T` from the pool (as `BootServicesData`)
+	///
+	/// UNSAFE: All-zeroes must be a valid `T` (e.g. a `#[repr(C)]` firmware structure of integers)
+	pub unsafe fn allocate_pool_zeroed<'a, T>(&'a self) -> Result<PoolBox<'a, T>, Status> {
+		PoolBox::new(self, mem::zeroed())
+	}
+
     pub fn allocate_pool<T>(&self, buffer_size: usize) -> Result<*mut T, Status>{
         let mut ptr: *mut Void = 0 as *mut Void;
         unsafe { (self.allocate_pool)(MemoryType::BootServicesData, buffer_size, &mut ptr) }
@@ -195,41 +337,457 @@ impl BootServices
             (self.free_pool)(p as *mut Void);
         }
 	}
+
+	/// Obtain the current size of the memory map (in bytes), and the size of each descriptor
+	///
+	/// The map can grow when memory is allocated (e.g. for the buffer passed to `get_memory_map`), so
+	/// leave space for a few extra descriptors.
+	pub fn memory_map_size(&self) -> Result<(usize, usize), Status> {
+		let mut size = 0;
+		let mut key = 0;
+		let mut descriptor_size = 0;
+		let mut descriptor_version = 0;
+		// SAFE: Zero-sized buffer, so nothing is written to it
+		match unsafe { (self.get_memory_map)(&mut size, ptr::null_mut(), &mut key, &mut descriptor_size, &mut descriptor_version) }
+		{
+		::status::SUCCESS | ::status::BUFFER_TOO_SMALL => Ok( (size, descriptor_size) ),
+		v => Err(v),
+		}
+	}
+
+	/// Fetch the current memory map into `buffer`
+	///
+	/// Returns `BUFFER_TOO_SMALL` if the map doesn't fit (see `memory_map_size`), and `UNSUPPORTED` if the
+	/// firmware's descriptor size isn't a multiple of 8 (so the descriptors couldn't be read in place)
+	pub fn get_memory_map<'a>(&self, buffer: &'a mut [u8]) -> Result<MemoryMap<'a>, Status> {
+		// Descriptors contain 64-bit fields, so skip to an aligned start
+		let ofs = buffer.as_ptr().align_offset(mem::align_of::<MemoryDescriptor>());
+		if ofs > buffer.len() {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		let buffer = &mut buffer[ofs..];
+		let mut size = buffer.len();
+		let mut key = 0;
+		let mut descriptor_size = 0;
+		let mut descriptor_version = 0;
+		// SAFE: Buffer is valid and aligned for `size` bytes
+		(unsafe { (self.get_memory_map)(&mut size, buffer.as_mut_ptr() as *mut MemoryDescriptor, &mut key, &mut descriptor_size, &mut descriptor_version) })?;
+		assert!(descriptor_size >= mem::size_of::<MemoryDescriptor>());
+		// Descriptors are read in place, so each one must stay aligned
+		if descriptor_size % mem::align_of::<MemoryDescriptor>() != 0 {
+			return Err(::status::UNSUPPORTED);
+		}
+		Ok(MemoryMap {
+			data: &buffer[..size],
+			key: key,
+			descriptor_size: descriptor_size,
+			descriptor_version: descriptor_version,
+			})
+	}
 }
 
+/// Image Services
 impl BootServices
 {
-	//#[inline]
-	//pub fn locate_handles_by_protocol(&self, protocol: &Guid) -> Result<PoolSlice<Handle>, Status> {
-	//	let mut ptr = 0 as *mut _;
-	//	let mut count = 0;
-	//	(self.locate_handle_buffer)(LocateSearchType::ByProtocol, Some(protocol), 0 as *const _, &mut count, &mut ptr)
-	//		.err_or_else(|| PoolSlice(ptr, count) )
-	//}
+	/// Set the unload callback for a loaded image (usually the caller's own image handle)
+	///
+	/// The callback is invoked by `UnloadImage` (e.g. the shell's `unload` command) at `TPL_APPLICATION`,
+	/// and is passed the image handle. It must undo everything the driver has installed (protocols, events,
+	/// allocations) and return `SUCCESS`, or return an error to refuse the unload. Since the image's code
+	/// is freed once it succeeds, nothing from the image (including the callback) may be used afterwards.
+	/// Notification functions at higher TPLs can still fire while it runs, so close events before tearing
+	/// down the state they use.
+	pub fn register_image_unload(&self, image: Handle, unload: protocols::ImageUnloadFcn) -> Result<(), Status> {
+		let mut ptr = ptr::null_mut();
+		// SAFE: Output pointer is valid
+		(unsafe { (self.handle_protocol)(image, &<protocols::LoadedImage as protocols::Protocol>::guid(), &mut ptr) })?;
+		// SAFE: The firmware returned a valid loaded image protocol, and the field is always writable by the image
+		unsafe {
+			(*(ptr as *mut protocols::LoadedImage)).unload = Some(unload);
+		}
+		Ok( () )
+	}
+
+	/// Exit the image, returning `exit_status` (and optional exit data) to whatever started it
+	///
+	/// By convention the exit data is a NUL-terminated UTF-16 (UCS-2) description of the failure - which the
+	/// shell prints - optionally followed by binary data. It's copied into a pool allocation (as the spec
+	/// requires), and omitted if that allocation fails.
+	pub fn exit(&self, image: Handle, exit_status: Status, exit_data: Option<&[u16]>) -> ! {
+		let (size, data) = match exit_data
+			{
+			Some(d) if !d.is_empty() => match self.allocate_pool::<u16>(d.len() * 2)
+				{
+				Ok(p) => {
+					// SAFE: Freshly allocated for `d.len()` code units (pool allocations are 8-byte aligned)
+					unsafe { ptr::copy_nonoverlapping(d.as_ptr(), p, d.len()); }
+					(d.len() * 2, p as *const u16)
+					},
+				Err(_) => (0, ptr::null()),
+				},
+			_ => (0, ptr::null()),
+			};
+		// SAFE: Exit data is null or a pool allocation (ownership is passed to the caller of the image)
+		let rv = unsafe { (self.exit)(image, exit_status, size, data) };
+		// Only returns on error (e.g. an invalid image handle)
+		panic!("BootServices::exit returned {:?}", rv);
+	}
+
+	/// Terminate boot services, handing control of the system to the caller
+	///
+	/// `map_key` must be from the most recent `get_memory_map` call. If the map has changed since then,
+	/// this returns `INVALID_PARAMETER` and the map must be fetched again (without any allocations in between).
+	/// See also `SystemTable::exit_boot_services`, which returns a `Runtime` handle for use afterwards.
+	///
+	/// UNSAFE: On success, boot services and every protocol obtained through them (including the console)
+	/// are gone, and must not be used again.
+	pub unsafe fn exit_boot_services(&self, image_handle: Handle, map_key: usize) -> Result<(), Status> {
+		(self.exit_boot_services)(image_handle, map_key)
+			.err_or( () )
+	}
+
+	/// Fetch the memory map into `buffer` and exit boot services, refetching and retrying if the map changed
+	///
+	/// Returns the final map (the one that the exit succeeded with). The map can change between the fetch and
+	/// the exit (e.g. a timer event allocating memory), which the firmware reports with `INVALID_PARAMETER`.
+	/// This retries at most `EXIT_BOOT_SERVICES_ATTEMPTS` times in total, then returns that error.
+	///
+	/// `buffer` must already be large enough (see `memory_map_size`) - it can't be allocated in here,
+	/// as an allocation changes the map, and after the first failed exit the firmware only permits
+	/// `get_memory_map` and `exit_boot_services` calls anyway.
+	///
+	/// UNSAFE: As for `exit_boot_services`. On failure, boot services may also be partially shut down.
+	pub unsafe fn exit_boot_services_loop<'a>(&self, image_handle: Handle, buffer: &'a mut [u8]) -> Result<MemoryMap<'a>, Status> {
+		let buffer: *mut [u8] = buffer;
+		for _ in 0 .. EXIT_BOOT_SERVICES_ATTEMPTS
+		{
+			// SAFE: Only one map borrows the buffer at a time (a failed attempt's map is dropped before the next)
+			let map = self.get_memory_map(&mut *buffer)?;
+			match self.exit_boot_services(image_handle, map.key())
+			{
+			Ok( () ) => return Ok(map),
+			Err(::status::INVALID_PARAMETER) => {},
+			Err(e) => return Err(e),
+			}
+		}
+		Err(::status::INVALID_PARAMETER)
+	}
+}
+
+impl BootServices
+{
+	/// Fill `buf` with the handles matching `search`, returning the number of handles found
+	///
+	/// If `buf` is too small, nothing is written and the required number of handles is returned instead
+	/// (i.e. the return value is larger than `buf.len()`). Unlike `locate_handles_by_protocol`, no firmware
+	/// allocation is made.
+	pub fn locate_handle(&self, search: SearchType, buf: &mut [Handle]) -> Result<usize, Status> {
+		let (ty, guid, key) = match search
+			{
+			SearchType::AllHandles => (LocateSearchType::AllHandles, None, ptr::null_mut()),
+			SearchType::ByRegisterNotify(key) => (LocateSearchType::ByRegisterNotify, None, key),
+			SearchType::ByProtocol(ref guid) => (LocateSearchType::ByProtocol, Some(guid), ptr::null_mut()),
+			};
+		// NOTE: The firmware takes and returns the buffer size in bytes
+		let mut size = buf.len() * mem::size_of::<Handle>();
+		// SAFE: Buffer is valid for `size` bytes, and the registration key isn't dereferenced by us
+		match unsafe { (self.locate_handle)(ty, guid, key, &mut size, buf.as_mut_ptr()) }
+		{
+		::status::SUCCESS | ::status::BUFFER_TOO_SMALL => Ok(size / mem::size_of::<Handle>()),
+		v => Err(v),
+		}
+	}
+
+	/// Obtain a list of every handle in the system (e.g. to walk all devices with `protocols_per_handle`)
+	///
+	/// The list is a pool allocation, freed when the returned buffer is dropped
+	pub fn all_handles<'a>(&'a self) -> Result<HandleBuffer<'a>, Status> {
+		let mut ptr = ptr::null_mut();
+		let mut count = 0;
+		// SAFE: Output pointers are valid
+		(unsafe { (self.locate_handle_buffer)(LocateSearchType::AllHandles, None, ptr::null(), &mut count, &mut ptr) })?;
+		assert!( !ptr.is_null() );
+		// SAFE: Pointer is to a pool allocation of `count` handles (as returned by the firmware)
+		Ok( unsafe { PoolVec::from_ptr(self, ptr, count, count) } )
+	}
+	/// Obtain the GUIDs of every protocol installed on `handle`
+	///
+	/// The list is a pool allocation (freed on drop), the GUIDs themselves belong to the firmware
+	pub fn protocols_per_handle<'a>(&'a self, handle: Handle) -> Result<PoolVec<'a, &'static Guid>, Status> {
+		let mut ptr = ptr::null_mut();
+		let mut count = 0;
+		// SAFE: Output pointers are valid
+		(unsafe { (self.protocols_per_handle)(handle, &mut ptr, &mut count) })?;
+		assert!( !ptr.is_null() );
+		// SAFE: Pointer is to a pool allocation of `count` GUID pointers (as returned by the firmware)
+		Ok( unsafe { PoolVec::from_ptr(self, ptr as *mut &'static Guid, count, count) } )
+	}
+	/// Obtain a list of all handles that support the specified protocol
+	pub fn locate_handles_by_protocol(&self, protocol: &Guid) -> Result<HandleBuffer, Status> {
+		let mut ptr = ptr::null_mut();
+		let mut count = 0;
+		// SAFE: Output pointers are valid
+		(unsafe { (self.locate_handle_buffer)(LocateSearchType::ByProtocol, Some(protocol), ptr::null(), &mut count, &mut ptr) })?;
+		assert!( !ptr.is_null() );
+		// SAFE: Pointer is to a pool allocation of `count` handles (as returned by the firmware)
+		Ok( unsafe { PoolVec::from_ptr(self, ptr, count, count) } )
+	}
+	/// List the agents (drivers or applications) that have `protocol` open on `handle`
+	pub fn open_protocol_information<'a>(&'a self, handle: Handle, protocol: &Guid) -> Result<PoolVec<'a, OpenProtocolInformationEntry>, Status> {
+		let mut ptr = ptr::null_mut();
+		let mut count = 0;
+		// SAFE: Output pointers are valid
+		(unsafe { (self.open_protocol_information)(handle, protocol, &mut ptr, &mut count) })?;
+		assert!( !ptr.is_null() );
+		// SAFE: Pointer is to a pool allocation of `count` entries (as returned by the firmware)
+		Ok( unsafe { PoolVec::from_ptr(self, ptr, count, count) } )
+	}
+	/// Iterate over every instance of a protocol (one per handle that supports it)
+	///
+	/// Yields nothing if the handles can't be obtained (e.g. `NOT_FOUND` when there are none)
+	pub fn all_protocols<'a, P: 'a + protocols::Protocol>(&'a self) -> impl Iterator<Item=&'a P> + 'a {
+		let handles = self.locate_handles_by_protocol(&P::guid()).ok();
+		handles.into_iter()
+			.flat_map(move |handles| (0 .. handles.len()).filter_map(move |i| self.handle_protocol::<P>(&handles[i]).ok()))
+	}
+
+	/// Locate the first instance of a protocol (EFI 1.10+, returns `UNSUPPORTED` on older firmware)
+	///
+	/// NOTE: When several instances exist (e.g. multiple GPUs or volumes), which one is returned is
+	/// arbitrary. Use `all_protocols` (or `locate_handles_by_protocol` and `handle_protocol`) to enumerate them.
 	pub fn locate_protocol<T: protocols::Protocol>(&self) -> Result<&'static T, Status> {
-        let guid = &T::guid();
-        let ptr : *mut Void = ptr::null_mut();
-        let mut interface = try!(self.allocate_pool::<T>(mem::size_of::<T>()));
-
-        let status = unsafe {
-            (self.locate_protocol)(guid, ptr, mem::transmute::<&mut *mut T, *mut *mut Void>(&mut interface))
-        };
-
-        if status == SUCCESS {
-            unsafe{ Ok(mem::transmute::<*mut T, &'static T>(interface)) }
-        } else {
-            self.free_pool::<T>(interface);
-            Err(status)
-        }
+		self.require_revision(::REVISION_1_10)?;
+		let mut ptr = ptr::null_mut();
+		// SAFE: Output pointer is valid, and the registration key is optional
+		(unsafe { (self.locate_protocol)(&T::guid(), ptr::null_mut(), &mut ptr) })?;
+		assert!(!ptr.is_null());
+		// SAFE: The firmware returned an instance of `T`
+		Ok( unsafe { &*T::from_ptr(ptr) } )
 	}
 	
+	/// Obtain a protocol interface from a handle
+	///
+	/// NOTE: This is the original EFI 1.10 interface, the firmware doesn't record the caller as a user
+	/// of the protocol, so nothing stops the interface from being uninstalled (e.g. by a driver being
+	/// disconnected) while the returned reference is still in use. Fine for protocols on the image's own
+	/// handle and other long-lived interfaces, otherwise prefer `open_protocol`/`handle_protocol_exclusive`.
 	pub fn handle_protocol<'a, P: 'a + protocols::Protocol>(&'a self, handle: &Handle) -> Result<&'a P, Status> {
 		let mut ptr = 0 as *mut Void;
 		// SAFE: Pointer cannot cause unsafety
 		unsafe { (self.handle_protocol)(*handle, &P::guid(), &mut ptr) }
 			.err_or_else( || unsafe { &*P::from_ptr(ptr) } )
 	}
+
+	/// Open a protocol interface on `handle` on behalf of `agent` (usually the image handle), closed when the
+	/// returned guard is dropped
+	///
+	/// `controller` is the controller handle for drivers (null for applications), and `attributes` is one
+	/// of the `OPEN_PROTOCOL_*` values. Unlike `handle_protocol`, the firmware records the open, so the
+	/// interface can't be uninstalled without the agent being told (or, for `BY_DRIVER`/`EXCLUSIVE`, at all).
+	///
+	/// NOTE: `OPEN_PROTOCOL_TEST_PROTOCOL` doesn't return an interface, use `test_protocol` for that.
+	pub fn open_protocol<'a, P: 'a + protocols::Protocol>(&'a self, handle: Handle, agent: Handle, controller: Handle, attributes: u32) -> Result<OpenProtocol<'a, P>, Status> {
+		if attributes == OPEN_PROTOCOL_TEST_PROTOCOL {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let mut ptr = ptr::null_mut();
+		// SAFE: Pointer cannot cause unsafety
+		unsafe { (self.open_protocol)(handle, &P::guid(), Some(&mut ptr), agent, controller, attributes)? };
+		assert!(!ptr.is_null());
+		Ok(OpenProtocol {
+			bs: self,
+			handle: handle,
+			agent: agent,
+			controller: controller,
+			// SAFE: The firmware returned an instance of `P`
+			ptr: unsafe { P::from_ptr(ptr) },
+			})
+	}
+	/// Open a protocol interface for exclusive use (`OPEN_PROTOCOL_EXCLUSIVE`), closed when the guard is dropped
+	///
+	/// Any drivers using the interface are disconnected first, and nothing else can open it with
+	/// `BY_DRIVER` or `EXCLUSIVE` until the guard is dropped (returns `ACCESS_DENIED` if it's already open
+	/// that way). This is the safe replacement for `handle_protocol` when the interface belongs to another
+	/// driver's handle.
+	pub fn handle_protocol_exclusive<'a, P: 'a + protocols::Protocol>(&'a self, handle: Handle, agent: Handle, controller: Handle) -> Result<OpenProtocol<'a, P>, Status> {
+		self.open_protocol(handle, agent, controller, OPEN_PROTOCOL_EXCLUSIVE)
+	}
+	/// Check whether `handle` supports the protocol `P` (`OPEN_PROTOCOL_TEST_PROTOCOL`)
+	pub fn test_protocol<P: protocols::Protocol>(&self, handle: Handle, agent: Handle, controller: Handle) -> bool {
+		// SAFE: No interface is returned
+		let rv = unsafe { (self.open_protocol)(handle, &P::guid(), None, agent, controller, OPEN_PROTOCOL_TEST_PROTOCOL) };
+		rv == SUCCESS
+	}
+
+	/// Install a protocol interface on a handle (or on a new handle if `handle` is `None`), returning the handle
+	///
+	/// UNSAFE: `interface` must point to a valid instance of the protocol identified by `guid`, and remain
+	/// valid until it's uninstalled (or forever, if it never is)
+	pub unsafe fn install_protocol_interface(&self, handle: Option<Handle>, guid: &Guid, interface: *mut Void) -> Result<Handle, Status> {
+		let mut handle = handle.unwrap_or(Handle::null());
+		(self.install_protocol_interface)(&mut handle, guid, InterfaceType::Native, interface)
+			.err_or(handle)
+	}
+
+	/// Install several protocol interfaces on a handle (or on a new handle if `handle` is `None`) as a single
+	/// operation, returning the handle
+	///
+	/// Unlike repeated `install_protocol_interface` calls, either every interface is installed or none are,
+	/// and the firmware refuses to create a second handle with an identical device path
+	/// (`ALREADY_STARTED`) - which is why drivers use this to install a device path alongside a protocol.
+	///
+	/// The firmware function is C-variadic (`handle, guid1, iface1, ..., NULL`). The x64 calling convention
+	/// passes variadic arguments exactly like fixed ones, so it's called through a fixed-arity signature
+	/// with the list padded out with nulls (the firmware stops at the first null GUID, and the caller owns
+	/// the argument space, so the extra arguments are harmless). This limits the call to
+	/// `INSTALL_MULTIPLE_MAX_PAIRS` pairs, more returns `INVALID_PARAMETER`.
+	///
+	/// UNSAFE: Each interface pointer must point to a valid instance of the protocol identified by its
+	/// GUID, and remain valid until it's uninstalled (or forever, if it never is)
+	pub unsafe fn install_multiple(&self, handle: Option<Handle>, pairs: &[(Guid, *const Void)]) -> Result<Handle, Status> {
+		type Fcn = efi_fcn!{ fn(*mut Handle,
+			*const Void, *const Void, *const Void, *const Void,
+			*const Void, *const Void, *const Void, *const Void,
+			*const Void, *const Void, *const Void, *const Void,
+			*const Void
+			) -> Status };
+		if pairs.len() > INSTALL_MULTIPLE_MAX_PAIRS {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		// GUID/interface pairs, followed by at least one null (the terminator)
+		let mut args = [ptr::null::<Void>(); INSTALL_MULTIPLE_MAX_PAIRS * 2 + 1];
+		for (i, &(ref guid, interface)) in pairs.iter().enumerate()
+		{
+			args[i * 2 + 0] = guid as *const Guid as *const Void;
+			args[i * 2 + 1] = interface;
+		}
+		let mut handle = handle.unwrap_or(Handle::null());
+		let fcn: Fcn = mem::transmute(self.install_multiple_protocol_interfaces);
+		fcn(&mut handle,
+			args[0], args[1], args[2], args[3],
+			args[4], args[5], args[6], args[7],
+			args[8], args[9], args[10], args[11],
+			args[12]
+			)
+			.err_or(handle)
+	}
+
+	/// Add, update, or remove (if `table` is null) an entry in the system table's configuration table list
+	///
+	/// NOTE: The firmware copies the GUID, but not the table data. `table` must remain valid (e.g. be in
+	/// runtime services memory if needed after `exit_boot_services`) for as long as it's installed.
+	pub fn install_configuration_table(&self, guid: &Guid, table: *const Void) -> Result<(), Status> {
+		// SAFE: The firmware doesn't dereference the table pointer
+		unsafe { (self.install_configuration_table)(guid, table as *mut Void) }
+			.err_or( () )
+	}
+
+	/// Request that `event` be signalled whenever an interface for `guid` is installed
+	///
+	/// Returns the registration key, which should be passed to `locate_handle` as `SearchType::ByRegisterNotify`
+	/// (after the event fires) to obtain the newly installed handles. The key is only meaningful in
+	/// combination with the event, and is released by the firmware when the event is closed.
+	pub fn register_protocol_notify(&self, guid: &Guid, event: Event) -> Result<*mut Void, Status> {
+		let mut registration = ptr::null_mut();
+		// SAFE: No memory unsafety, the event handle can only have come from a successful `create_event*`
+		unsafe { (self.register_protocol_notify)(guid, event, &mut registration) }
+			.err_or(registration)
+	}
 }
+
+/// Driver Support Services
+impl BootServices
+{
+	/// Connect all available drivers to a controller
+	///
+	/// If `recursive` is set, child controllers created by the drivers are also connected (and so on down
+	/// the tree), otherwise only the immediate controller is connected.
+	pub fn connect_controller(&self, controller: Handle, recursive: bool) -> Result<(), Status> {
+		// SAFE: Null driver list and device path are permitted
+		unsafe { (self.connect_controller)(controller, ptr::null(), None, recursive) }
+			.err_or( () )
+	}
+
+	/// Connect specific drivers to a controller
+	///
+	/// UNSAFE: `driver_image_handles` must be null, or point to a null-terminated array of image handles
+	pub unsafe fn connect_controller_with(&self, controller: Handle, driver_image_handles: *const Handle, remaining_device_path: Option<&DevicePath>, recursive: bool) -> Result<(), Status> {
+		(self.connect_controller)(controller, driver_image_handles, remaining_device_path, recursive)
+			.err_or( () )
+	}
+
+	/// Disconnect all drivers from a controller (also destroying any child controllers)
+	pub fn disconnect_controller(&self, controller: Handle) -> Result<(), Status> {
+		// SAFE: Null driver/child handles are permitted
+		unsafe { (self.disconnect_controller)(controller, Handle::null(), Handle::null()) }
+			.err_or( () )
+	}
+}
+
+/// Miscellaneous Services
+impl BootServices
+{
+	/// Busy-wait for at least the specified number of microseconds
+	pub fn stall(&self, microseconds: usize) -> Result<(), Status> {
+		// SAFE: No memory unsafety
+		unsafe { (self.stall)(microseconds) }
+			.err_or( () )
+	}
+
+	/// Set (or with a timeout of 0, disable) the watchdog timer
+	///
+	/// The firmware arms a 5 minute watchdog before starting a boot option, which resets the system if it
+	/// expires. Loaders that wait for user input (or OSes that stay in boot services) should disable or extend it.
+	pub fn set_watchdog_timer(&self, timeout_seconds: usize) -> Result<(), Status> {
+		// SAFE: No watchdog data passed
+		unsafe { (self.set_watchdog_timer)(timeout_seconds, 0, 0, ptr::null()) }
+			.err_or( () )
+	}
+
+	/// Raise the task priority level, returning the previous level (to pass to `restore_tpl`)
+	///
+	/// UNSAFE: `tpl` must not be lower than the current level, and `restore_tpl` must be called with the
+	/// returned value before returning to the firmware.
+	pub unsafe fn raise_tpl(&self, tpl: Tpl) -> Tpl {
+		(self.raise_tpl)(tpl)
+	}
+	/// Restore the task priority level after `raise_tpl`
+	///
+	/// UNSAFE: `old_tpl` must be the value returned by the matching `raise_tpl`
+	pub unsafe fn restore_tpl(&self, old_tpl: Tpl) {
+		(self.restore_tpl)(old_tpl)
+	}
+
+	/// Obtain the next value of the platform's monotonic counter
+	///
+	/// Only valid before `exit_boot_services`, use `RuntimeServices::next_high_monotonic_count` afterwards.
+	pub fn get_next_monotonic_count(&self) -> Result<u64, Status> {
+		let mut rv = 0;
+		// SAFE: No memory unsafety
+		unsafe { (self.get_next_monotonic_count)(&mut rv) }
+			.err_or(rv)
+	}
+
+	/// Calculate the CRC32 of a buffer using the firmware's implementation
+	///
+	/// Returns `UNSUPPORTED` on older firmware that doesn't provide this service
+	pub fn calculate_crc32(&self, data: &[u8]) -> Result<u32, Status> {
+		self.require_revision(::REVISION_1_10)?;
+		let f = match self.calculate_crc32
+			{
+			Some(f) => f,
+			None => return Err(::status::UNSUPPORTED),
+			};
+		let mut rv = 0;
+		// SAFE: Pointer and length are from a valid slice, and the data isn't mutated
+		unsafe { f(data.as_ptr() as *const Void, data.len(), &mut rv) }
+			.err_or(rv)
+	}
+}
+
+/// Pool-allocated list of handles returned by `LocateHandleBuffer`
+pub type HandleBuffer<'a> = PoolVec<'a, Handle>;
+
 /// Owned vector from the UEFI general pool
 pub struct PoolVec<'a, T>
 {
@@ -283,8 +841,265 @@ impl<'a,T> ::core::ops::Drop for PoolVec<'a, T>
 	}
 }
 
-// TODO: Make a wrapper around an array of MemoryDescriptor
+/// Single value in the UEFI general pool, freed on drop
+///
+/// Like `Owned`, but for memory allocated by the caller (see `BootServices::allocate_pool_typed`)
+pub struct PoolBox<'a, T>
+{
+	bs: &'a BootServices,
+	ptr: ::core::ptr::Unique<T>,
+}
+impl<'a, T> PoolBox<'a, T>
+{
+	/// Move `value` into a new pool allocation
+	pub fn new(bs: &'a BootServices, value: T) -> Result<PoolBox<'a, T>, Status> {
+		// NOTE: AllocatePool returns 8-byte aligned data (and may reject a zero-sized allocation)
+		assert!(mem::align_of::<T>() <= 8);
+		let p = bs.allocate_pool::<T>(::core::cmp::max(mem::size_of::<T>(), 1))?;
+		// SAFE: Freshly allocated, with the correct size and alignment
+		unsafe {
+			ptr::write(p, value);
+			Ok(PoolBox {
+				bs: bs,
+				ptr: ::core::ptr::Unique::new_unchecked(p),
+				})
+		}
+	}
+	/// Relinquish ownership, returning the raw pointer (which must later be passed to `free_pool`)
+	pub fn into_raw(self) -> *mut T {
+		let rv = self.ptr.as_ptr();
+		mem::forget(self);
+		rv
+	}
+}
+impl<'a, T> ::core::ops::Deref for PoolBox<'a, T>
+{
+	type Target = T;
+	fn deref(&self) -> &T {
+		// SAFE: Owned and initialised
+		unsafe { &*self.ptr.as_ptr() }
+	}
+}
+impl<'a, T> ::core::ops::DerefMut for PoolBox<'a, T>
+{
+	fn deref_mut(&mut self) -> &mut T {
+		// SAFE: Owned and initialised
+		unsafe { &mut *self.ptr.as_ptr() }
+	}
+}
+impl<'a, T> ::core::ops::Drop for PoolBox<'a, T>
+{
+	fn drop(&mut self) {
+		// SAFE: Owned, and only dropped/freed once
+		unsafe {
+			ptr::drop_in_place(self.ptr.as_ptr());
+		}
+		self.bs.free_pool(self.ptr.as_ptr());
+	}
+}
+
+/// Protocol interface opened with `BootServices::open_protocol`, closed on drop
+pub struct OpenProtocol<'a, P: 'a + protocols::Protocol>
+{
+	bs: &'a BootServices,
+	handle: Handle,
+	agent: Handle,
+	controller: Handle,
+	ptr: *const P,
+}
+impl<'a, P: 'a + protocols::Protocol> OpenProtocol<'a, P>
+{
+	/// Handle the protocol was opened on
+	pub fn handle(&self) -> Handle {
+		self.handle
+	}
+}
+impl<'a, P: 'a + protocols::Protocol> ::core::ops::Deref for OpenProtocol<'a, P>
+{
+	type Target = P;
+	fn deref(&self) -> &P {
+		// SAFE: The interface can't be uninstalled while it's open
+		unsafe { &*self.ptr }
+	}
+}
+impl<'a, P: 'a + protocols::Protocol> ::core::ops::Drop for OpenProtocol<'a, P>
+{
+	fn drop(&mut self) {
+		// SAFE: Opened with these handles in `open_protocol`, and only closed once
+		let _ = unsafe { (self.bs.close_protocol)(self.handle, &P::guid(), self.agent, self.controller) };
+	}
+}
+
+/// Firmware-provided object that must be released once no longer needed
+pub trait Release
+{
+	/// Release the object (e.g. free the pool allocation, or close the handle)
+	///
+	/// UNSAFE: Must be called at most once, on an object obtained from the firmware
+	unsafe fn release(ptr: *mut Self, bs: &BootServices);
+}
+
+/// Owned firmware object (e.g. a pool-allocated device path), released on drop
+pub struct Owned<'a, T: 'a + ?Sized + Release>
+{
+	bs: &'a BootServices,
+	ptr: ::core::ptr::Unique<T>,
+}
+impl<'a, T: 'a + ?Sized + Release> Owned<'a, T>
+{
+	/// UNSAFE: Pointer must be non-null, valid, and owned by the caller
+	pub unsafe fn from_ptr(bs: &BootServices, p: *mut T) -> Owned<T> {
+		Owned {
+			bs: bs,
+			ptr: ::core::ptr::Unique::new_unchecked(p),
+			}
+	}
+	/// Boot services instance used to release this object
+	pub fn boot_services(&self) -> &'a BootServices {
+		self.bs
+	}
+	/// Relinquish ownership, returning the raw pointer (which is no longer released on drop)
+	pub fn into_raw(self) -> *mut T {
+		let rv = self.ptr.as_ptr();
+		::core::mem::forget(self);
+		rv
+	}
+}
+impl<'a, T: 'a + ?Sized + Release> ::core::ops::Deref for Owned<'a, T>
+{
+	type Target = T;
+	fn deref(&self) -> &T {
+		// SAFE: Pointer is valid and owned
+		unsafe { &*self.ptr.as_ptr() }
+	}
+}
+impl<'a, T: 'a + ?Sized + Release> ::core::ops::DerefMut for Owned<'a, T>
+{
+	fn deref_mut(&mut self) -> &mut T {
+		// SAFE: Pointer is valid and uniquely owned
+		unsafe { &mut *self.ptr.as_ptr() }
+	}
+}
+impl<'a, T: 'a + ?Sized + Release> ::core::ops::Drop for Owned<'a, T>
+{
+	fn drop(&mut self) {
+		// SAFE: Owned, and only released once
+		unsafe {
+			T::release(self.ptr.as_ptr(), self.bs);
+		}
+	}
+}
+
+/// Memory map obtained from `BootServices::get_memory_map`
+pub struct MemoryMap<'a>
+{
+	data: &'a [u8],
+	key: usize,
+	descriptor_size: usize,
+	descriptor_version: u32,
+}
+impl<'a> MemoryMap<'a>
+{
+	/// Key to pass to `exit_boot_services` (only valid until the map next changes)
+	pub fn key(&self) -> usize {
+		self.key
+	}
+	pub fn descriptor_version(&self) -> u32 {
+		self.descriptor_version
+	}
+	/// Number of descriptors in the map
+	pub fn len(&self) -> usize {
+		self.data.len() / self.descriptor_size
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	/// Write the map as a table, e.g. to `EfiLogger` or a `SerialPort` (see `MemoryMapIter::print`)
+	pub fn print<W: ::core::fmt::Write>(&self, out: &mut W) -> ::core::fmt::Result {
+		self.iter().print(out)
+	}
+	/// Iterate the descriptors (which may be larger than `MemoryDescriptor`, so can't be a slice)
+	pub fn iter(&self) -> MemoryMapIter<'a> {
+		MemoryMapIter {
+			data: self.data,
+			descriptor_size: self.descriptor_size,
+			}
+	}
+}
+impl<'a> IntoIterator for &'a MemoryMap<'a>
+{
+	type Item = &'a MemoryDescriptor;
+	type IntoIter = MemoryMapIter<'a>;
+	fn into_iter(self) -> MemoryMapIter<'a> {
+		self.iter()
+	}
+}
+/// Iterator over the descriptors in a `MemoryMap`
+pub struct MemoryMapIter<'a>
+{
+	data: &'a [u8],
+	descriptor_size: usize,
+}
+impl<'a> MemoryMapIter<'a>
+{
+	/// Write the remaining descriptors as a table (one line each, see `MemoryDescriptor`'s `Display`)
+	pub fn print<W: ::core::fmt::Write>(self, out: &mut W) -> ::core::fmt::Result {
+		writeln!(out, "{:<23} {:<33} {:>10} {:>9}  {}", "Type", "Physical range", "Pages", "Size", "Attributes")?;
+		for desc in self
+		{
+			writeln!(out, "{}", desc)?;
+		}
+		Ok( () )
+	}
+
+	/// Copy the remaining descriptors into `buf`, merging adjacent entries with the same type and attributes
+	///
+	/// Returns the used part of `buf`. Merged entries keep the first entry's `virtual_start`. The input must
+	/// be sorted by physical address (the spec doesn't require this, but firmware produces sorted maps in
+	/// practice): `INVALID_PARAMETER` is returned if an entry starts before the previous one ends, and
+	/// `BUFFER_TOO_SMALL` if the merged map doesn't fit.
+	pub fn coalesce<'b>(self, buf: &'b mut [MemoryDescriptor]) -> Result<&'b [MemoryDescriptor], Status> {
+		let mut len = 0;
+		for desc in self
+		{
+			if len > 0 {
+				let last = &mut buf[len - 1];
+				if desc.physical_start < last.physical_end() {
+					return Err(::status::INVALID_PARAMETER);
+				}
+				if desc.physical_start == last.physical_end() && desc.ty == last.ty && desc.attribute == last.attribute {
+					last.number_of_pages += desc.number_of_pages;
+					continue ;
+				}
+			}
+			if len == buf.len() {
+				return Err(::status::BUFFER_TOO_SMALL);
+			}
+			buf[len] = *desc;
+			len += 1;
+		}
+		Ok(&buf[..len])
+	}
+}
+impl<'a> Iterator for MemoryMapIter<'a>
+{
+	type Item = &'a MemoryDescriptor;
+	fn next(&mut self) -> Option<&'a MemoryDescriptor> {
+		if self.data.len() < self.descriptor_size {
+			None
+		}
+		else {
+			// SAFE: Start is aligned (checked in `get_memory_map`, as is the stride being a multiple of 8), and
+			// there's at least `descriptor_size >= size_of::<MemoryDescriptor>()` bytes available
+			let rv = unsafe { &*(self.data.as_ptr() as *const MemoryDescriptor) };
+			self.data = &self.data[self.descriptor_size..];
+			Some(rv)
+		}
+	}
+}
+
 #[repr(C)]
+#[derive(Copy, Clone, Default)]
 pub struct MemoryDescriptor
 {
 	pub ty: u32,
@@ -293,7 +1108,150 @@ pub struct MemoryDescriptor
 	pub virtual_start: VirtualAddress,
 	pub number_of_pages: u64,
 	pub attribute: u64,
-	_pad2: u64,
+}
+// `EFI_MEMORY_DESCRIPTOR` is 40 bytes (the firmware's `descriptor_size` may be larger, for future fields)
+#[allow(dead_code)]
+const MEMORY_DESCRIPTOR_SIZE_CHECK: [(); 40] = [(); mem::size_of::<MemoryDescriptor>()];
+impl MemoryDescriptor
+{
+	/// Typed view of `attribute`
+	pub fn attributes(&self) -> MemoryAttribute {
+		MemoryAttribute(self.attribute)
+	}
+	/// Name of the memory type (e.g. `ConventionalMemory`), or `None` for OEM/OS-defined types
+	pub fn type_name(&self) -> Option<&'static str> {
+		const NAMES: [&'static str; 14] = [
+			"ReservedMemoryType", "LoaderCode", "LoaderData", "BootServicesCode", "BootServicesData",
+			"RuntimeServicesCode", "RuntimeServicesData", "ConventionalMemory", "UnusableMemory",
+			"AcpiReclaimMemory", "AcpiMemoryNVS", "MemoryMappedIO", "MemoryMappedIOPortSpace", "PalCode",
+			];
+		NAMES.get(self.ty as usize).map(|v| *v)
+	}
+	/// Physical address just past the end of the region
+	pub fn physical_end(&self) -> PhysicalAddress {
+		self.physical_start.wrapping_add(self.number_of_pages.wrapping_mul(0x1000))
+	}
+}
+/// Single table row: type, physical range (inclusive), page count, size, and attributes
+///
+/// e.g. `ConventionalMemory      0000000000100000-00000000007fffff       1792     7 MiB  UC|WC|WT|WB`
+impl ::core::fmt::Display for MemoryDescriptor
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		match self.type_name()
+		{
+		Some(n) => write!(f, "{:<23}", n)?,
+		None => write!(f, "{:<23}", self.ty)?,
+		}
+		write!(f, " {:016x}-{:016x} {:>10} ", self.physical_start, self.physical_end().wrapping_sub(1), self.number_of_pages)?;
+		// Largest unit that the size is at least one of (rounded down)
+		let bytes = self.number_of_pages.saturating_mul(0x1000);
+		let (v, unit) = if bytes >= 1 << 40 { (bytes >> 40, "TiB") }
+			else if bytes >= 1 << 30 { (bytes >> 30, "GiB") }
+			else if bytes >= 1 << 20 { (bytes >> 20, "MiB") }
+			else { (bytes >> 10, "KiB") };
+		write!(f, "{:>5} {}  {}", v, unit, self.attributes())
+	}
+}
+impl ::core::fmt::Debug for MemoryDescriptor
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		f.debug_struct("MemoryDescriptor")
+			.field("ty", &self.type_name().unwrap_or("?"))
+			.field("physical_start", &format_args!("{:#x}", self.physical_start))
+			.field("virtual_start", &format_args!("{:#x}", self.virtual_start))
+			.field("number_of_pages", &self.number_of_pages)
+			.field("attribute", &self.attributes())
+			.finish()
+	}
+}
+
+/// Memory descriptor attribute bits (capabilities of the region, and whether it's needed at runtime)
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct MemoryAttribute(pub u64);
+impl MemoryAttribute
+{
+	/// Uncacheable
+	pub const UC: MemoryAttribute = MemoryAttribute(0x1);
+	/// Write-combining
+	pub const WC: MemoryAttribute = MemoryAttribute(0x2);
+	/// Write-through
+	pub const WT: MemoryAttribute = MemoryAttribute(0x4);
+	/// Write-back
+	pub const WB: MemoryAttribute = MemoryAttribute(0x8);
+	/// Uncacheable, exported, supports the "fetch and add" semaphore mechanism
+	pub const UCE: MemoryAttribute = MemoryAttribute(0x10);
+	/// Write-protect capable
+	pub const WP: MemoryAttribute = MemoryAttribute(0x1000);
+	/// Read-protect capable
+	pub const RP: MemoryAttribute = MemoryAttribute(0x2000);
+	/// Execute-protect capable
+	pub const XP: MemoryAttribute = MemoryAttribute(0x4000);
+	/// Persistent (non-volatile) memory
+	pub const NV: MemoryAttribute = MemoryAttribute(0x8000);
+	/// Higher reliability than other memory in the system
+	pub const MORE_RELIABLE: MemoryAttribute = MemoryAttribute(0x10000);
+	/// Read-only capable
+	pub const RO: MemoryAttribute = MemoryAttribute(0x20000);
+	/// Specific-purpose memory (e.g. reserved for a particular device)
+	pub const SP: MemoryAttribute = MemoryAttribute(0x40000);
+	/// Protected by the CPU's memory cryptographic capabilities
+	pub const CPU_CRYPTO: MemoryAttribute = MemoryAttribute(0x80000);
+	/// Used by runtime services, so needs a virtual mapping (see `SetVirtualAddressMap`)
+	pub const RUNTIME: MemoryAttribute = MemoryAttribute(1 << 63);
+
+	pub fn empty() -> MemoryAttribute {
+		MemoryAttribute(0)
+	}
+	/// Returns true if all bits in `other` are set
+	pub fn contains(&self, other: MemoryAttribute) -> bool {
+		self.0 & other.0 == other.0
+	}
+	/// Returns true if the region must be mapped for runtime services after `exit_boot_services`
+	pub fn is_runtime(&self) -> bool {
+		self.contains(MemoryAttribute::RUNTIME)
+	}
+}
+impl ::core::ops::BitOr for MemoryAttribute
+{
+	type Output = MemoryAttribute;
+	fn bitor(self, other: MemoryAttribute) -> MemoryAttribute {
+		MemoryAttribute(self.0 | other.0)
+	}
+}
+impl ::core::fmt::Debug for MemoryAttribute
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		write!(f, "MemoryAttribute({})", self)
+	}
+}
+/// Attribute names joined with `|` (e.g. `UC|WC|WB|RUNTIME`), with any unknown bits in hex
+impl ::core::fmt::Display for MemoryAttribute
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		const NAMES: [(MemoryAttribute, &'static str); 14] = [
+			(MemoryAttribute::UC, "UC"), (MemoryAttribute::WC, "WC"), (MemoryAttribute::WT, "WT"), (MemoryAttribute::WB, "WB"),
+			(MemoryAttribute::UCE, "UCE"), (MemoryAttribute::WP, "WP"), (MemoryAttribute::RP, "RP"), (MemoryAttribute::XP, "XP"),
+			(MemoryAttribute::NV, "NV"), (MemoryAttribute::MORE_RELIABLE, "MORE_RELIABLE"), (MemoryAttribute::RO, "RO"),
+			(MemoryAttribute::SP, "SP"), (MemoryAttribute::CPU_CRYPTO, "CPU_CRYPTO"), (MemoryAttribute::RUNTIME, "RUNTIME"),
+			];
+		let mut rem = self.0;
+		let mut first = true;
+		for &(v, name) in NAMES.iter()
+		{
+			if self.contains(v) {
+				if !first { f.write_str("|")?; }
+				f.write_str(name)?;
+				rem &= !v.0;
+				first = false;
+			}
+		}
+		if rem != 0 || first {
+			if !first { f.write_str("|")?; }
+			write!(f, "{:#x}", rem)?;
+		}
+		Ok( () )
+	}
 }
 #[repr(C)]
 pub enum MemoryType
@@ -322,6 +1280,17 @@ pub enum TimerDelay
 	Periodic,
 	Relative,
 }
+/// Entry returned by `open_protocol_information` (an agent that has a protocol open)
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct OpenProtocolInformationEntry
+{
+	pub agent_handle: Handle,
+	pub controller_handle: Handle,
+	pub attributes: u32,
+	pub open_count: u32,
+}
+
 #[repr(C)]
 pub enum AllocateType
 {
@@ -335,6 +1304,16 @@ pub enum InterfaceType
 	Native,
 	PCode,
 }
+/// Search criteria for `BootServices::locate_handle`
+#[derive(Copy, Clone, Debug)]
+pub enum SearchType
+{
+	AllHandles,
+	/// Handles newly installed for the protocol notification with this registration key (see
+	/// `register_protocol_notify`), returned one at a time
+	ByRegisterNotify(*mut Void),
+	ByProtocol(Guid),
+}
 #[repr(C)]
 pub enum LocateSearchType
 {
@@ -0,0 +1,78 @@
+//! Events with a Rust closure as the notify function
+use core::{mem, ptr};
+use super::{BootServices, Event, EventNotifyFcn, Tpl, raw};
+use {Status, Void};
+
+/// Event that calls a pool-allocated closure when notified, closed (and the closure dropped) on drop
+///
+/// Created by `BootServices::create_event_closure`
+pub struct OwnedEvent<'a>
+{
+	bs: &'a BootServices,
+	event: Event,
+	closure: *mut Void,
+	drop_closure: unsafe fn(*mut Void),
+}
+impl<'a> OwnedEvent<'a>
+{
+	pub(super) fn new<F>(bs: &'a BootServices, ty: u32, notify_tpl: Tpl, f: F) -> Result<OwnedEvent<'a>, Status>
+	where
+		F: FnMut() + Send + 'static
+	{
+		// NOTE: AllocatePool returns 8-byte aligned data (and may reject a zero-sized allocation)
+		assert!(mem::align_of::<F>() <= 8);
+		let closure = bs.allocate_pool::<F>(::core::cmp::max(mem::size_of::<F>(), 1))?;
+		// SAFE: Freshly allocated, with the correct size and alignment
+		unsafe { ptr::write(closure, f); }
+		// SAFE: `Event` is a wrapper around `raw::Event`, and calling an `extern fn` through an unsafe fn pointer is fine
+		let nf: EventNotifyFcn = unsafe { mem::transmute(trampoline::<F> as extern "win64" fn(raw::Event, *mut Void)) };
+		match bs.create_event(ty, notify_tpl, Some( (nf, closure as *mut Void) ))
+		{
+		Ok(event) => Ok(OwnedEvent {
+			bs: bs,
+			event: event,
+			closure: closure as *mut Void,
+			drop_closure: drop_closure::<F>,
+			}),
+		Err(e) => {
+			// SAFE: The event wasn't created, so nothing else refers to the closure
+			unsafe {
+				drop_closure::<F>(closure as *mut Void);
+			}
+			bs.free_pool(closure);
+			Err(e)
+			},
+		}
+	}
+
+	/// Underlying event handle (e.g. for `signal_event` or `set_timer`)
+	///
+	/// The handle is closed when this is dropped, so don't keep copies of it past that.
+	pub fn event(&self) -> Event {
+		self.event
+	}
+}
+impl<'a> Drop for OwnedEvent<'a>
+{
+	fn drop(&mut self) {
+		// Close first, so the firmware can't call the closure once it's gone
+		self.bs.close_event(self.event);
+		// SAFE: The event is closed, and this is the only remaining reference to the closure
+		unsafe {
+			(self.drop_closure)(self.closure);
+		}
+		self.bs.free_pool(self.closure);
+	}
+}
+
+/// Notify function passed to the firmware, calls the closure stored in `context`
+extern "win64" fn trampoline<F: FnMut()>(_event: raw::Event, context: *mut Void) {
+	// SAFE: `context` is the `F` written by `OwnedEvent::new`, which outlives the event. The firmware doesn't
+	// re-enter a notify function while it's running (notifications are queued at the event's TPL).
+	let f = unsafe { &mut *(context as *mut F) };
+	f();
+}
+
+unsafe fn drop_closure<F>(p: *mut Void) {
+	ptr::drop_in_place(p as *mut F);
+}
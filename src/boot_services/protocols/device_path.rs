@@ -17,19 +17,123 @@ impl DevicePath
 	fn data_ptr(&self) -> *const u8 {
 		(self.length.as_ptr() as usize + 4) as *const u8
 	}
+	/// Total length of this node in bytes (including the 4-byte header)
 	#[inline]
-	fn data_len(&self) -> usize {
+	pub fn node_len(&self) -> usize {
 		self.length[0] as usize + self.length[1] as usize * 256
 	}
 	#[inline]
+	fn data_len(&self) -> usize {
+		self.node_len().saturating_sub(4)
+	}
+	#[inline]
 	fn data(&self) -> &[u8] {
 		unsafe {
 			::core::slice::from_raw_parts(self.data_ptr(), self.data_len())
 		}
 	}
+	/// Raw bytes of this node (header and data)
+	pub fn node_bytes(&self) -> &[u8] {
+		// SAFE: (assumed) Firmware-provided nodes are valid for their reported length
+		unsafe {
+			::core::slice::from_raw_parts(self as *const DevicePath as *const u8, ::core::cmp::max(self.node_len(), 4))
+		}
+	}
+
+	/// Returns true if this is an End node (type 0x7F), either end of instance (sub-type 0x01) or end of the
+	/// entire path (sub-type 0xFF)
+	#[inline]
+	pub fn is_end(&self) -> bool {
+		self.ty == END_TYPE
+	}
+
+	/// Total length of the path in bytes, including every instance and the final End node
+	///
+	/// This is the size to copy when duplicating the path. Returns 0 (never a valid length) if the path
+	/// is malformed: a node shorter than its header, or no End-of-entire-path node within
+	/// `MAX_TOTAL_LEN` bytes.
+	pub fn total_len(&self) -> usize {
+		let mut len = 0;
+		let mut node = self;
+		loop
+		{
+			let node_len = node.node_len();
+			if node_len < 4 || len + node_len > MAX_TOTAL_LEN {
+				return 0;
+			}
+			len += node_len;
+			if node.ty == END_TYPE && node.sub_type == END_ENTIRE_SUBTYPE {
+				return len;
+			}
+			// SAFE: (assumed) Firmware-provided nodes are valid for their reported length, and the walk is bounded
+			node = unsafe { &*((node as *const DevicePath as *const u8).offset(node_len as isize) as *const DevicePath) };
+		}
+	}
+
+	/// Iterate the nodes of this path (the first instance, if there are several), not including the End node
+	pub fn nodes<'a>(&'a self) -> Nodes<'a> {
+		Nodes(Some(self))
+	}
+
+	/// Returns true if every node of `prefix` matches the start of this path
+	///
+	/// The End node isn't compared, so e.g. a disk's path is a prefix of the paths of its partitions
+	pub fn starts_with(&self, prefix: &DevicePath) -> bool {
+		let mut nodes = self.nodes();
+		prefix.nodes().all(|p| match nodes.next()
+			{
+			Some(n) => n.node_bytes() == p.node_bytes(),
+			None => false,
+			})
+	}
+	/// Returns true if both paths have the same nodes
+	pub fn eq_path(&self, other: &DevicePath) -> bool {
+		self.starts_with(other) && other.starts_with(self)
+	}
+}
+
+/// Device path node type for End nodes
+///
+/// The sub-type says which end it is: 0x01 separates the instances of a multi-instance path, and
+/// `END_ENTIRE_SUBTYPE` (0xFF) terminates the whole path.
+const END_TYPE: u8 = 0x7F;
+/// End node sub-type: end of the entire path
+const END_ENTIRE_SUBTYPE: u8 = 0xFF;
+/// Upper bound on the length walked by `DevicePath::total_len`, so a missing terminator can't run off
+/// into unrelated memory forever
+const MAX_TOTAL_LEN: usize = 0x1_0000;
+
+/// Iterator over the nodes in a device path (see `DevicePath::nodes`)
+pub struct Nodes<'a>(Option<&'a DevicePath>);
+impl<'a> Iterator for Nodes<'a>
+{
+	type Item = &'a DevicePath;
+	fn next(&mut self) -> Option<&'a DevicePath> {
+		match self.0
+		{
+		Some(n) if !n.is_end() && n.node_len() >= 4 => {
+			// SAFE: (assumed) Firmware-provided paths are correctly terminated by an End node
+			self.0 = Some(unsafe { &*((n as *const DevicePath as *const u8).offset(n.node_len() as isize) as *const DevicePath) });
+			Some(n)
+			},
+		// End node, or a malformed length (which would loop forever)
+		_ => {
+			self.0 = None;
+			None
+			},
+		}
+	}
 }
 
 
+/// Device paths returned by the firmware (e.g. from `DevicePathUtilities`) are pool allocations
+impl ::boot_services::Release for DevicePath
+{
+	unsafe fn release(ptr: *mut DevicePath, bs: &::boot_services::BootServices) {
+		bs.free_pool(ptr);
+	}
+}
+
 impl super::Protocol for DevicePath
 {
 	fn guid() -> ::Guid {
@@ -0,0 +1,57 @@
+///
+///
+///
+use {Status,Guid,Void,CStr16,CStr16Ptr};
+use boot_services::{BootServices, Owned};
+use super::DevicePath;
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x05c99a21,0xc70f,0x4ad2,[0x8a,0x5f,0x35,0xdf,0x33,0x43,0xf5,0x1e]);
+
+#[repr(C)]
+pub struct DevicePathFromText
+{
+	pub convert_text_to_device_node: efi_fcn!{ fn(CStr16Ptr) -> *mut DevicePath },
+	pub convert_text_to_device_path: efi_fcn!{ fn(CStr16Ptr) -> *mut DevicePath },
+}
+impl super::Protocol for DevicePathFromText
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const DevicePathFromText
+	}
+}
+
+impl DevicePathFromText
+{
+	/// Parse a textual device path (e.g. `PciRoot(0x0)/Pci(0x1,0x0)/HD(1,GPT,...)`)
+	///
+	/// The result is freed when the returned `Owned` is dropped. Returns `INVALID_PARAMETER` if the firmware
+	/// couldn't convert the text (it doesn't distinguish this from running out of memory).
+	pub fn convert_text_to_device_path<'a>(&self, bs: &'a BootServices, text: &CStr16) -> Result<Owned<'a, DevicePath>, Status> {
+		// SAFE: NUL terminated valid pointer
+		let p = unsafe { (self.convert_text_to_device_path)(text.as_ptr()) };
+		if p.is_null() {
+			Err(::status::INVALID_PARAMETER)
+		}
+		else {
+			// SAFE: Non-null pool allocation owned by the caller
+			Ok( unsafe { Owned::from_ptr(bs, p) } )
+		}
+	}
+
+	/// Parse a single textual device node (e.g. `Pci(0x1,0x0)`)
+	pub fn convert_text_to_device_node<'a>(&self, bs: &'a BootServices, text: &CStr16) -> Result<Owned<'a, DevicePath>, Status> {
+		// SAFE: NUL terminated valid pointer
+		let p = unsafe { (self.convert_text_to_device_node)(text.as_ptr()) };
+		if p.is_null() {
+			Err(::status::INVALID_PARAMETER)
+		}
+		else {
+			// SAFE: Non-null pool allocation owned by the caller
+			Ok( unsafe { Owned::from_ptr(bs, p) } )
+		}
+	}
+}
@@ -0,0 +1,77 @@
+///
+///
+///
+use {Status,Guid,Void};
+use boot_services::{BootServices, Owned};
+use super::DevicePath;
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x0379be4e,0xd706,0x437d,[0xb0,0x37,0xed,0xb8,0x2f,0xb7,0x72,0xa4]);
+
+#[repr(C)]
+pub struct DevicePathUtilities
+{
+	pub get_device_path_size: efi_fcn!{ fn(*const DevicePath) -> usize },
+	pub duplicate_device_path: efi_fcn!{ fn(*const DevicePath) -> *mut DevicePath },
+	pub append_device_path: efi_fcn!{ fn(*const DevicePath, *const DevicePath) -> *mut DevicePath },
+	pub append_device_node: efi_fcn!{ fn(*const DevicePath, *const DevicePath) -> *mut DevicePath },
+	pub append_device_path_instance: efi_fcn!{ fn(*const DevicePath, *const DevicePath) -> *mut DevicePath },
+	pub get_next_device_path_instance: efi_fcn!{ fn(&mut *mut DevicePath, &mut usize) -> *mut DevicePath },
+	pub is_device_path_multi_instance: efi_fcn!{ fn(*const DevicePath) -> bool },
+	pub create_device_node: efi_fcn!{ fn(/*node_type:*/ u8, /*node_sub_type:*/ u8, /*node_length:*/ u16) -> *mut DevicePath },
+}
+impl super::Protocol for DevicePathUtilities
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const DevicePathUtilities
+	}
+}
+
+/// Wrap a firmware-allocated device path, mapping null to `OUT_OF_RESOURCES`
+unsafe fn owned_path(bs: &BootServices, p: *mut DevicePath) -> Result<Owned<DevicePath>, Status> {
+	if p.is_null() {
+		Err(::status::OUT_OF_RESOURCES)
+	}
+	else {
+		Ok( Owned::from_ptr(bs, p) )
+	}
+}
+
+impl DevicePathUtilities
+{
+	/// Size of a device path in bytes (including the end node)
+	pub fn get_device_path_size(&self, path: &DevicePath) -> usize {
+		// SAFE: Valid device path
+		unsafe { (self.get_device_path_size)(path) }
+	}
+
+	/// Create a pool-allocated copy of a device path
+	pub fn duplicate_device_path<'a>(&self, bs: &'a BootServices, path: &DevicePath) -> Result<Owned<'a, DevicePath>, Status> {
+		// SAFE: Valid device path, result is owned by the caller
+		unsafe { owned_path(bs, (self.duplicate_device_path)(path)) }
+	}
+
+	/// Create a new device path by appending `second` to `first`
+	pub fn append_device_path<'a>(&self, bs: &'a BootServices, first: &DevicePath, second: &DevicePath) -> Result<Owned<'a, DevicePath>, Status> {
+		// SAFE: Valid device paths, result is owned by the caller
+		unsafe { owned_path(bs, (self.append_device_path)(first, second)) }
+	}
+
+	/// Create a new device path by appending a single node to `path`
+	pub fn append_device_node<'a>(&self, bs: &'a BootServices, path: &DevicePath, node: &DevicePath) -> Result<Owned<'a, DevicePath>, Status> {
+		// SAFE: Valid device path and node, result is owned by the caller
+		unsafe { owned_path(bs, (self.append_device_node)(path, node)) }
+	}
+
+	/// Allocate a (zeroed) device node with the specified type and total length (including the 4-byte header)
+	pub fn create_device_node<'a>(&self, bs: &'a BootServices, ty: u8, sub_type: u8, length: u16) -> Result<Owned<'a, DevicePath>, Status> {
+		if length < 4 {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		// SAFE: No memory unsafety, result is owned by the caller
+		unsafe { owned_path(bs, (self.create_device_node)(ty, sub_type, length)) }
+	}
+}
@@ -1,5 +1,7 @@
+use core::{mem, ptr};
 use runtime_services::Time;
-use {Status, Guid};
+use boot_services::{BootServices, Owned, Release};
+use {Status, Guid, Str16, CStr16, ArrayCStr16};
 
 pub const FILE_MODE_READ: u64 = 1;
 pub const FILE_MODE_WRITE: u64 = 2;
@@ -12,6 +14,82 @@ pub const FILE_RESERVED: u64 = 0x08;
 pub const FILE_DIRECTORY: u64 = 0x10;
 pub const FILE_ARCHIVE: u64 = 0x20;
 
+/// File attribute bits (`FileInfo::Attribute`, and the `attributes` argument of `File::open`)
+///
+/// Attributes can be built up for `File::set_info` with `|` or `with`/`without`, e.g.
+/// `info.set_attributes(info.attributes().with(Attributes::HIDDEN).without(Attributes::ARCHIVE))`
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Attributes(pub u64);
+impl Attributes
+{
+	pub const READ_ONLY: Attributes = Attributes(FILE_READ_ONLY);
+	pub const HIDDEN: Attributes = Attributes(FILE_HIDDEN);
+	pub const SYSTEM: Attributes = Attributes(FILE_SYSTEM);
+	pub const RESERVED: Attributes = Attributes(FILE_RESERVED);
+	pub const DIRECTORY: Attributes = Attributes(FILE_DIRECTORY);
+	/// Modified since last backed up (set by the file system on writes)
+	pub const ARCHIVE: Attributes = Attributes(FILE_ARCHIVE);
+
+	pub fn empty() -> Attributes {
+		Attributes(0)
+	}
+	/// Returns true if all bits in `other` are set
+	pub fn contains(&self, other: Attributes) -> bool {
+		self.0 & other.0 == other.0
+	}
+	pub fn is_directory(&self) -> bool {
+		self.contains(Attributes::DIRECTORY)
+	}
+	pub fn is_read_only(&self) -> bool {
+		self.contains(Attributes::READ_ONLY)
+	}
+	pub fn is_hidden(&self) -> bool {
+		self.contains(Attributes::HIDDEN)
+	}
+	/// Set the bits in `other`
+	pub fn with(self, other: Attributes) -> Attributes {
+		Attributes(self.0 | other.0)
+	}
+	/// Clear the bits in `other`
+	pub fn without(self, other: Attributes) -> Attributes {
+		Attributes(self.0 & !other.0)
+	}
+}
+impl ::core::ops::BitOr for Attributes
+{
+	type Output = Attributes;
+	fn bitor(self, other: Attributes) -> Attributes {
+		Attributes(self.0 | other.0)
+	}
+}
+/// Lists the set flags, e.g. `Attributes(READ_ONLY|DIRECTORY)`, with any unknown bits in hex
+impl ::core::fmt::Debug for Attributes
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		const NAMES: [(Attributes, &'static str); 6] = [
+			(Attributes::READ_ONLY, "READ_ONLY"), (Attributes::HIDDEN, "HIDDEN"), (Attributes::SYSTEM, "SYSTEM"),
+			(Attributes::RESERVED, "RESERVED"), (Attributes::DIRECTORY, "DIRECTORY"), (Attributes::ARCHIVE, "ARCHIVE"),
+			];
+		f.write_str("Attributes(")?;
+		let mut rem = self.0;
+		let mut first = true;
+		for &(v, name) in NAMES.iter()
+		{
+			if self.contains(v) {
+				if !first { f.write_str("|")?; }
+				f.write_str(name)?;
+				rem &= !v.0;
+				first = false;
+			}
+		}
+		if rem != 0 {
+			if !first { f.write_str("|")?; }
+			write!(f, "{:#x}", rem)?;
+		}
+		f.write_str(")")
+	}
+}
+
 #[repr(C)]
 pub struct FileInfo {
     pub Size: u64,
@@ -39,6 +117,91 @@ impl Default for FileInfo {
     }
 }
 
+impl FileInfo {
+    /// File name (up to the first NUL, or the whole array if there isn't one)
+    pub fn name(&self) -> &Str16 {
+        CStr16::from_bounded_units(&self.FileName)
+    }
+    pub fn is_directory(&self) -> bool {
+        self.attributes().is_directory()
+    }
+    pub fn attributes(&self) -> Attributes {
+        Attributes(self.Attribute)
+    }
+    /// Replace the attributes (applied with `File::set_info`)
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.Attribute = attributes.0;
+    }
+    /// Replace the file name (e.g. to rename with `File::set_info`)
+    ///
+    /// Returns `INVALID_PARAMETER` if the name contains NUL, and `BUFFER_TOO_SMALL` if it doesn't fit
+    pub fn set_name(&mut self, name: &str) -> Result<(), Status> {
+        if name.chars().any(|c| c == '\0') {
+            return Err(::status::INVALID_PARAMETER);
+        }
+        if ::utf16_len(name) + 1 > self.FileName.len() {
+            return Err(::status::BUFFER_TOO_SMALL);
+        }
+        for (d, s) in self.FileName.iter_mut().zip(name.encode_utf16().chain(::core::iter::repeat(0))) {
+            *d = s;
+        }
+        Ok( () )
+    }
+
+    /// Number of bytes of this structure that the firmware uses (the fixed fields, then the name and its NUL)
+    fn used_size(&self) -> Option<usize> {
+        let name_len = self.FileName.iter().position(|&c| c == 0)?;
+        Some(mem::size_of::<FileInfo>() - mem::size_of_val(&self.FileName) + (name_len + 1) * 2)
+    }
+}
+
+/// Volume information (`EFI_FILE_SYSTEM_INFO`), from `File::filesystem_info`
+///
+/// The label is variable-length in the firmware's structure, this holds up to 255 code units of it.
+#[repr(C)]
+pub struct FileSystemInfo {
+    pub Size: u64,
+    pub ReadOnly: bool,
+    pub VolumeSize: u64,
+    pub FreeSpace: u64,
+    pub BlockSize: u32,
+    pub VolumeLabel: [u16; 256],
+}
+
+impl Default for FileSystemInfo {
+    fn default() -> Self {
+        FileSystemInfo {
+            Size: 0,
+            ReadOnly: false,
+            VolumeSize: 0,
+            FreeSpace: 0,
+            BlockSize: 0,
+            VolumeLabel: [0; 256],
+        }
+    }
+}
+
+impl FileSystemInfo {
+    /// Volume label (up to the first NUL, or the whole array if there isn't one)
+    pub fn label(&self) -> &Str16 {
+        CStr16::from_bounded_units(&self.VolumeLabel)
+    }
+}
+
+/// Volume label alone (`EFI_FILE_SYSTEM_VOLUME_LABEL`), from `File::volume_label`
+///
+/// Like `FileSystemInfo`, holds up to 255 code units of the label.
+#[repr(C)]
+pub struct FileSystemVolumeLabel {
+    pub VolumeLabel: [u16; 256],
+}
+
+impl FileSystemVolumeLabel {
+    pub fn label(&self) -> &Str16 {
+        CStr16::from_bounded_units(&self.VolumeLabel)
+    }
+}
+
 #[repr(C)]
 pub struct File
 {
@@ -51,6 +214,166 @@ pub struct File
 	pub get_position: efi_fcn!{ fn(&File, &mut u64) -> Status },
 	pub set_position: efi_fcn!{ fn(&mut File, u64) -> Status },
     pub get_info: efi_fcn!{ fn(&mut File, &Guid, &mut usize, *mut u8) -> Status },
-    pub set_info: efi_fcn!{ fn(&mut File, &Guid, &mut usize, *const u8) -> Status },
+    pub set_info: efi_fcn!{ fn(&mut File, &Guid, /*buffer_size:*/ usize, *const u8) -> Status },
     pub flush: efi_fcn!{ fn(&mut File) -> Status }
 }
+
+/// Maximum length (in code units, including the NUL) of a single component passed to `File::open_path`
+const MAX_COMPONENT_LEN: usize = 256;
+
+impl Release for File
+{
+	unsafe fn release(ptr: *mut File, _bs: &BootServices) {
+		((*ptr).close)(&mut *ptr);
+	}
+}
+
+impl File
+{
+	/// Open a file or directory relative to this directory (`attributes` are only used when creating)
+	pub fn open<'a>(&self, bs: &'a BootServices, name: &CStr16, mode: u64, attributes: u64) -> Result<Owned<'a, File>, Status> {
+		let mut ptr = ptr::null_mut();
+		// SAFE: Name is NUL terminated, output pointer is valid
+		(unsafe { (self.open)(self, &mut ptr, name.as_ptr(), mode, attributes) })?;
+		// SAFE: A successful open returns a new handle, which is now owned
+		Ok( unsafe { Owned::from_ptr(bs, ptr) } )
+	}
+
+	/// Get the size, timestamps, attributes, and name of this file
+	///
+	/// Returns `BUFFER_TOO_SMALL` if the name is longer than `FileInfo` can hold
+	pub fn info(&mut self) -> Result<FileInfo, Status> {
+		let mut rv = FileInfo::default();
+		let mut size = mem::size_of::<FileInfo>();
+		// SAFE: Buffer is a valid `FileInfo`, of the passed size
+		(unsafe { (self.get_info)(self, &::FILE_INFO_ID, &mut size, &mut rv as *mut FileInfo as *mut u8) })?;
+		Ok(rv)
+	}
+	/// Update this file's information, e.g. to rename it (change `FileName`) or change its attributes
+	///
+	/// `Size` is filled in here from the file name's length. The firmware only allows renaming within the
+	/// same directory (the new name can't contain a path), may reject some attribute changes (e.g. setting
+	/// `FILE_DIRECTORY`) with `ACCESS_DENIED`, and truncates/extends the file if `FileSize` is changed.
+	/// Returns `INVALID_PARAMETER` if `FileName` isn't NUL terminated.
+	pub fn set_info(&mut self, info: &FileInfo) -> Result<(), Status> {
+		let size = info.used_size().ok_or(::status::INVALID_PARAMETER)?;
+		// SAFE: `FileInfo` is plain data (no destructor), so a bitwise copy is fine
+		let mut tmp: FileInfo = unsafe { ptr::read(info) };
+		tmp.Size = size as u64;
+		// SAFE: Buffer is valid for `size` bytes, and isn't modified
+		(unsafe { (self.set_info)(self, &::FILE_INFO_ID, size, &tmp as *const FileInfo as *const u8) })
+			.err_or( () )
+	}
+
+	/// Get the size, free space, and label of the volume containing this file
+	///
+	/// Returns `BUFFER_TOO_SMALL` if the label is longer than `FileSystemInfo` can hold
+	pub fn filesystem_info(&mut self) -> Result<FileSystemInfo, Status> {
+		let mut rv = FileSystemInfo::default();
+		let mut size = mem::size_of::<FileSystemInfo>();
+		// SAFE: Buffer is a valid `FileSystemInfo`, of the passed size
+		(unsafe { (self.get_info)(self, &::FILE_SYSTEM_INFO_ID, &mut size, &mut rv as *mut FileSystemInfo as *mut u8) })?;
+		Ok(rv)
+	}
+	/// Get the label of the volume containing this file
+	pub fn volume_label(&mut self) -> Result<FileSystemVolumeLabel, Status> {
+		let mut rv = FileSystemVolumeLabel { VolumeLabel: [0; 256] };
+		let mut size = mem::size_of::<FileSystemVolumeLabel>();
+		// SAFE: Buffer is a valid `FileSystemVolumeLabel`, of the passed size
+		(unsafe { (self.get_info)(self, &::FILE_SYSTEM_VOLUME_LABEL_ID, &mut size, &mut rv as *mut FileSystemVolumeLabel as *mut u8) })?;
+		Ok(rv)
+	}
+	/// Change the label of the volume containing this file (at most 255 code units)
+	///
+	/// Passes only the label and its NUL to the firmware (the size is that of the label, not of
+	/// `FileSystemVolumeLabel`). Read-only volumes return `WRITE_PROTECTED`.
+	pub fn set_volume_label(&mut self, label: &str) -> Result<(), Status> {
+		let mut buf = ArrayCStr16::<256>::new();
+		buf.push_str(label)?;
+		// NOTE: The size is passed by value (unlike `get_info`, which updates it)
+		let size = (buf.len() + 1) * 2;
+		// SAFE: Buffer is valid for `size` bytes (the label and NUL), and isn't modified
+		(unsafe { (self.set_info)(self, &::FILE_SYSTEM_VOLUME_LABEL_ID, size, buf.as_cstr16().as_ptr() as *const u8) })
+			.err_or( () )
+	}
+
+	/// Open a second, independent handle to this directory (by opening `.` relative to it)
+	///
+	/// The new handle has its own position, e.g. for scanning a directory while still holding the original.
+	/// Whether this works depends on the firmware: most only support it on directories. Returns `UNSUPPORTED`
+	/// if the firmware doesn't accept `.` here.
+	pub fn duplicate<'a>(&self, bs: &'a BootServices, mode: u64) -> Result<Owned<'a, File>, Status> {
+		let mut name = ArrayCStr16::<2>::new();
+		name.push_char('.')?;
+		match self.open(bs, name.as_cstr16(), mode, 0)
+		{
+		Err(::status::NOT_FOUND) | Err(::status::INVALID_PARAMETER) => Err(::status::UNSUPPORTED),
+		rv => rv,
+		}
+	}
+
+	/// Read data from the current position, returning the number of bytes read (0 at the end of the file)
+	///
+	/// On a directory, each read returns one `FileInfo` entry (or `BUFFER_TOO_SMALL` if it doesn't fit).
+	pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Status> {
+		let mut len = buf.len();
+		// SAFE: Buffer is valid for `len` bytes
+		(unsafe { (self.read)(self, &mut len, buf.as_mut_ptr() as *mut ::Void) })?;
+		Ok(len)
+	}
+
+	/// Write data at the current position, returning the number of bytes written
+	pub fn write(&mut self, data: &[u8]) -> Result<usize, Status> {
+		let mut len = data.len();
+		// SAFE: Buffer is valid for `len` bytes, and isn't modified
+		(unsafe { (self.write)(self, &mut len, data.as_ptr() as *const ::Void) })?;
+		Ok(len)
+	}
+
+	/// Open a file by a path relative to this directory, e.g. `root.open_path(bs, "EFI\\BOOT\\BOOTX64.EFI", FILE_MODE_READ)`
+	///
+	/// UEFI paths are separated by backslashes (`\`), and empty components are ignored. Each component is
+	/// opened in turn (intermediate directories read-only, `mode` only applies to the final component), and
+	/// intermediate handles are closed along the way. `.` components are skipped, and `..` is passed to the
+	/// firmware (which resolves it to the parent of the directory it's opened from).
+	pub fn open_path<'a>(&self, bs: &'a BootServices, path: &str, mode: u64) -> Result<Owned<'a, File>, Status> {
+		let mut components = path.split('\\').filter(|c| !c.is_empty() && *c != ".").peekable();
+		let mut cur: Option<Owned<'a, File>> = None;
+		while let Some(component) = components.next()
+		{
+			let mut name = ArrayCStr16::<MAX_COMPONENT_LEN>::new();
+			name.push_str(component)?;
+			let step_mode = if components.peek().is_some() { FILE_MODE_READ } else { mode };
+			let next = {
+				let dir: &File = match cur
+					{
+					Some(ref f) => f,
+					None => self,
+					};
+				dir.open(bs, name.as_cstr16(), step_mode, 0)?
+				};
+			// Drops (closes) the previous intermediate directory
+			cur = Some(next);
+		}
+		match cur
+		{
+		Some(f) => Ok(f),
+		// Empty path - open a new handle to this directory
+		None => self.duplicate(bs, mode),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::FileInfo;
+
+	#[test]
+	fn name_without_nul() {
+		let mut info = FileInfo::default();
+		info.FileName = [b'a' as u16; 256];
+		assert_eq!(info.name().chars().count(), 256);
+		assert!(info.used_size().is_none());
+	}
+}
@@ -42,6 +42,76 @@ impl GraphicsOutput
 		(self.set_mode)(self, index).err_or(())
 	}
 
+	/// Capture the current mode's framebuffer parameters
+	///
+	/// This is the supported way to use graphics after `exit_boot_services`: the protocol (and its `mode`
+	/// pointer) becomes invalid after exit, but the framebuffer itself remains. Take the snapshot before
+	/// exiting (and don't change mode afterwards), then use it with `::framebuffer::Framebuffer::from_info`,
+	/// or pass it on to the kernel.
+	pub fn snapshot(&self) -> FramebufferInfo {
+		let mode = self.mode;
+		// SAFE: (assumed) The firmware provides a valid mode information structure
+		let info = unsafe { &*mode.info };
+		FramebufferInfo {
+			base: mode.frame_buffer_base,
+			size: mode.frame_buffer_size as u64,
+			pixels_per_scanline: info.pixels_per_scanline,
+			horizontal_resolution: info.horizontal_resolution,
+			vertical_resolution: info.vertical_resolution,
+			pixel_format: info.pixel_format,
+			pixel_information: info.pixel_information,
+			}
+	}
+
+	/// Set the mode with exactly `width`x`height`, or if there isn't one, the smallest (by pixel count)
+	/// mode that's at least that large in both dimensions
+	///
+	/// If several modes share the chosen resolution, a linear framebuffer format is preferred (`RGBX`/`BGRX`,
+	/// then `BitMask`, then `BltOnly`), then the lowest mode number. Returns `NOT_FOUND` if no mode is large enough.
+	pub fn set_mode_by_resolution(&self, width: u32, height: u32) -> Result<(), Status> {
+		let index = self.best_mode(|info| {
+			if info.horizontal_resolution < width || info.vertical_resolution < height {
+				None
+			}
+			else {
+				let exact = info.horizontal_resolution == width && info.vertical_resolution == height;
+				Some( (!exact as u64, pixel_count(info)) )
+			}
+			})?;
+		self.set_mode(index)
+	}
+	/// Set the mode with the most pixels (with the same tie-breaking as `set_mode_by_resolution`)
+	pub fn set_highest_resolution(&self) -> Result<(), Status> {
+		let index = self.best_mode(|info| Some( (0, !pixel_count(info)) ))?;
+		self.set_mode(index)
+	}
+	/// Find the mode with the lowest `key` (then the preferred pixel format, then the lowest index)
+	fn best_mode<F: Fn(&ModeInformation) -> Option<(u64, u64)>>(&self, key: F) -> Result<u32, Status> {
+		let mut best = None;
+		for index in 0 .. self.mode.max_mode
+		{
+			let info = match self.query_mode(index)
+				{
+				Ok(v) => v,
+				Err(_) => continue,
+				};
+			if let Some( (a, b) ) = key(&info) {
+				let format_rank = match info.pixel_format
+					{
+					PixelFormat::RGBX | PixelFormat::BGRX => 0,
+					PixelFormat::BitMask => 1,
+					PixelFormat::BltOnly => 2,
+					};
+				let k = (a, b, format_rank);
+				// Strict comparison, so the lowest index wins ties
+				if best.map(|(bk, _)| k < bk).unwrap_or(true) {
+					best = Some( (k, index) );
+				}
+			}
+		}
+		best.map(|(_, index)| index).ok_or(::status::NOT_FOUND)
+	}
+
 	pub fn iter_modes(&self) -> ModeIter {
 		ModeIter(self, 0)
 	}
@@ -58,6 +128,23 @@ impl GraphicsOutput
 	pub fn blt_inner_video(&self, src_x: usize, src_y: usize,  width: usize, height: usize,  dst_x: usize, dst_y: usize) {
 		let _ = (self.blt)(self, ::core::ptr::null_mut(), BltOperation::VideoToVideo, width, height, src_x,src_y, dst_x,dst_y, 0);
 	}
+
+	/// Copy the whole screen into `buf` (row-major, `horizontal_resolution` pixels per row)
+	///
+	/// Returns `BUFFER_TOO_SMALL` if `buf` can't hold the current mode's resolution
+	pub fn capture(&self, buf: &mut [BltPixel]) -> Result<(), Status> {
+		// SAFE: (assumed) The firmware provides a valid mode information structure
+		let info = unsafe { &*self.mode.info };
+		let (w, h) = (info.horizontal_resolution as usize, info.vertical_resolution as usize);
+		if buf.len() < w * h {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		(self.blt)(self, buf.as_mut_ptr(), BltOperation::VideoToBltBuffer, 0,0, 0,0, w,h, 0).err_or( () )
+	}
+}
+
+fn pixel_count(info: &ModeInformation) -> u64 {
+	info.horizontal_resolution as u64 * info.vertical_resolution as u64
 }
 
 pub struct ModeIter<'a>(&'a GraphicsOutput, u32);
@@ -86,13 +173,78 @@ pub struct ModeInformation
 	pub pixels_per_scanline: u32,
 }
 
+/// Framebuffer parameters captured by `GraphicsOutput::snapshot` (plain data, suitable for a kernel hand-off)
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct FramebufferInfo
+{
+	/// Physical address of the framebuffer
+	pub base: ::PhysicalAddress,
+	/// Size of the framebuffer in bytes
+	pub size: u64,
+	/// Pitch in pixels (may be larger than `horizontal_resolution`)
+	pub pixels_per_scanline: u32,
+	pub horizontal_resolution: u32,
+	pub vertical_resolution: u32,
+	pub pixel_format: PixelFormat,
+	/// Channel masks (only meaningful if `pixel_format` is `BitMask`)
+	pub pixel_information: PixelBitmask,
+}
+
+#[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct PixelBitmask
 {
-	red_mask: u32,
-	green_mask: u32,
-	blue_mask: u32,
-	reserved_mask: u32,
+	pub red_mask: u32,
+	pub green_mask: u32,
+	pub blue_mask: u32,
+	pub reserved_mask: u32,
+}
+
+/// Pack a pixel into the 32-bit value used by a framebuffer of the given format
+///
+/// For `BitMask`, each channel is scaled to the width of its mask (e.g. 5 or 6 bits for 565). Returns 0 for
+/// `BltOnly`, which has no framebuffer layout.
+pub fn encode_pixel(px: BltPixel, format: PixelFormat, masks: &PixelBitmask) -> u32 {
+	fn scale(v: u8, mask: u32) -> u32 {
+		if mask == 0 {
+			return 0;
+		}
+		let shift = mask.trailing_zeros();
+		let bits = (mask >> shift).count_ones();
+		let v = if bits >= 8 { (v as u32) << (bits - 8) } else { v as u32 >> (8 - bits) };
+		(v << shift) & mask
+	}
+	match format
+	{
+	PixelFormat::RGBX => px.red as u32 | (px.green as u32) << 8 | (px.blue as u32) << 16,
+	PixelFormat::BGRX => px.blue as u32 | (px.green as u32) << 8 | (px.red as u32) << 16,
+	PixelFormat::BitMask => scale(px.red, masks.red_mask) | scale(px.green, masks.green_mask) | scale(px.blue, masks.blue_mask),
+	PixelFormat::BltOnly => 0,
+	}
+}
+
+/// Pack pixels into the byte layout of a framebuffer with the given format (four bytes per pixel)
+///
+/// `masks` is only used for `BitMask` (pass the mode's `pixel_information`). Returns `UNSUPPORTED` for
+/// `BltOnly`, and `BUFFER_TOO_SMALL` if `out` is shorter than `4 * src.len()`.
+pub fn convert_pixels(src: &[BltPixel], dst_format: PixelFormat, masks: &PixelBitmask, out: &mut [u8]) -> Result<(), Status> {
+	if dst_format == PixelFormat::BltOnly {
+		return Err(::status::UNSUPPORTED);
+	}
+	if out.len() < src.len() * 4 {
+		return Err(::status::BUFFER_TOO_SMALL);
+	}
+	for (px, d) in src.iter().zip(out.chunks_mut(4))
+	{
+		// Framebuffers are little-endian, so the first channel's byte comes first
+		let v = encode_pixel(*px, dst_format, masks);
+		d[0] = v as u8;
+		d[1] = (v >> 8) as u8;
+		d[2] = (v >> 16) as u8;
+		d[3] = (v >> 24) as u8;
+	}
+	Ok( () )
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -116,13 +268,46 @@ pub struct Mode
 	pub frame_buffer_size: usize,
 }
 
+/// Pixel as used by `blt` (blue, green, red, reserved - matching the firmware's layout)
+///
+/// `reserved` is ignored by the firmware, and should be left as zero (the constructors here do so).
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 pub struct BltPixel
 {
-	blue: u8,
-	green: u8,
-	red: u8,
-	reserved: u8,
+	pub blue: u8,
+	pub green: u8,
+	pub red: u8,
+	pub reserved: u8,
+}
+impl BltPixel
+{
+	pub const BLACK: BltPixel = BltPixel { blue: 0x00, green: 0x00, red: 0x00, reserved: 0 };
+	pub const WHITE: BltPixel = BltPixel { blue: 0xFF, green: 0xFF, red: 0xFF, reserved: 0 };
+	pub const GREY: BltPixel = BltPixel { blue: 0x80, green: 0x80, red: 0x80, reserved: 0 };
+	pub const RED: BltPixel = BltPixel { blue: 0x00, green: 0x00, red: 0xFF, reserved: 0 };
+	pub const GREEN: BltPixel = BltPixel { blue: 0x00, green: 0xFF, red: 0x00, reserved: 0 };
+	pub const BLUE: BltPixel = BltPixel { blue: 0xFF, green: 0x00, red: 0x00, reserved: 0 };
+	pub const YELLOW: BltPixel = BltPixel { blue: 0x00, green: 0xFF, red: 0xFF, reserved: 0 };
+	pub const CYAN: BltPixel = BltPixel { blue: 0xFF, green: 0xFF, red: 0x00, reserved: 0 };
+	pub const MAGENTA: BltPixel = BltPixel { blue: 0xFF, green: 0x00, red: 0xFF, reserved: 0 };
+
+	pub fn rgb(red: u8, green: u8, blue: u8) -> BltPixel {
+		BltPixel {
+			blue: blue,
+			green: green,
+			red: red,
+			reserved: 0,
+			}
+	}
+	/// Construct from a `0xRRGGBB` value (the top byte is ignored)
+	pub fn from_hex(rgb: u32) -> BltPixel {
+		BltPixel::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+	}
+	/// Pack as `0xRRGGBB`
+	pub fn to_hex(&self) -> u32 {
+		(self.red as u32) << 16 | (self.green as u32) << 8 | self.blue as u32
+	}
 }
 
 #[repr(C)]
@@ -134,3 +319,40 @@ pub enum BltOperation
 	VideoToVideo,
 }
 
+
+#[cfg(test)]
+mod tests
+{
+	use super::{BltPixel, PixelFormat, PixelBitmask, encode_pixel, convert_pixels};
+
+	const NO_MASKS: PixelBitmask = PixelBitmask { red_mask: 0, green_mask: 0, blue_mask: 0, reserved_mask: 0 };
+	const MASKS_565: PixelBitmask = PixelBitmask { red_mask: 0xF800, green_mask: 0x07E0, blue_mask: 0x001F, reserved_mask: 0 };
+
+	#[test]
+	fn rgb_and_bgr() {
+		let px = BltPixel::rgb(0x11, 0x22, 0x33);
+		let mut out = [0; 4];
+		convert_pixels(&[px], PixelFormat::RGBX, &NO_MASKS, &mut out).unwrap();
+		assert_eq!(out, [0x11, 0x22, 0x33, 0]);
+		convert_pixels(&[px], PixelFormat::BGRX, &NO_MASKS, &mut out).unwrap();
+		assert_eq!(out, [0x33, 0x22, 0x11, 0]);
+	}
+
+	#[test]
+	fn bitmask_565() {
+		assert_eq!(encode_pixel(BltPixel::WHITE, PixelFormat::BitMask, &MASKS_565), 0xFFFF);
+		assert_eq!(encode_pixel(BltPixel::RED, PixelFormat::BitMask, &MASKS_565), 0xF800);
+		assert_eq!(encode_pixel(BltPixel::GREEN, PixelFormat::BitMask, &MASKS_565), 0x07E0);
+		assert_eq!(encode_pixel(BltPixel::rgb(0x08, 0x04, 0x08), PixelFormat::BitMask, &MASKS_565), 0x0821);
+		let mut out = [0; 8];
+		convert_pixels(&[BltPixel::BLUE, BltPixel::WHITE], PixelFormat::BitMask, &MASKS_565, &mut out).unwrap();
+		assert_eq!(out, [0x1F, 0x00, 0, 0, 0xFF, 0xFF, 0, 0]);
+	}
+
+	#[test]
+	fn convert_errors() {
+		let mut out = [0; 4];
+		assert!(convert_pixels(&[BltPixel::BLACK; 2], PixelFormat::RGBX, &NO_MASKS, &mut out) == Err(::status::BUFFER_TOO_SMALL));
+		assert!(convert_pixels(&[BltPixel::BLACK], PixelFormat::BltOnly, &NO_MASKS, &mut out) == Err(::status::UNSUPPORTED));
+	}
+}
@@ -0,0 +1,94 @@
+///
+///
+///
+use {Status, Guid, Void};
+
+/// Protocol GUID
+///
+/// NOTE: Instances are usually created through the Hash2 service binding protocol, but many firmwares also
+/// install a ready-to-use instance that `locate_protocol` finds.
+pub const GUID: Guid = Guid(0x55b1d734,0xc5e1,0x49db,[0x96,0x47,0xb1,0x6a,0xfb,0x0e,0x30,0x5b]);
+
+// Algorithm GUIDs
+pub const HASH_ALGORITHM_SHA1_GUID: Guid = Guid(0x2ae9d80f,0x3fb2,0x4095,[0xb7,0xb1,0xe9,0x31,0x57,0xb9,0x46,0xb6]);
+pub const HASH_ALGORITHM_SHA256_GUID: Guid = Guid(0x51aa59de,0xfdf2,0x4ea3,[0xbc,0x63,0x87,0x5f,0xb7,0x84,0x2e,0xe9]);
+pub const HASH_ALGORITHM_SHA384_GUID: Guid = Guid(0xefa96432,0xde33,0x4dd2,[0xae,0xe6,0x32,0x8c,0x33,0xdf,0x77,0x7a]);
+pub const HASH_ALGORITHM_SHA512_GUID: Guid = Guid(0xcaa4381e,0x750c,0x4770,[0xb8,0x70,0x7a,0x23,0xb4,0xe4,0x21,0x30]);
+
+/// `EFI_HASH2_OUTPUT` (a union of the digest types, large enough for SHA-512)
+pub type Hash2Output = [u8; 64];
+
+#[repr(C)]
+pub struct Hash2
+{
+	pub get_hash_size: efi_fcn!{ fn(&Hash2, /*hash_algorithm:*/ &Guid, /*hash_size:*/ &mut usize) -> Status },
+	pub hash: efi_fcn!{ fn(&Hash2, &Guid, /*message:*/ *const u8, /*message_size:*/ usize, /*hash:*/ &mut Hash2Output) -> Status },
+	pub hash_init: efi_fcn!{ fn(&Hash2, &Guid) -> Status },
+	pub hash_update: efi_fcn!{ fn(&Hash2, /*message:*/ *const u8, /*message_size:*/ usize) -> Status },
+	pub hash_final: efi_fcn!{ fn(&Hash2, /*hash:*/ &mut Hash2Output) -> Status },
+}
+impl super::Protocol for Hash2
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const Hash2
+	}
+}
+
+impl Hash2
+{
+	/// Size of the digest produced by the algorithm (`UNSUPPORTED` if the firmware doesn't implement it)
+	pub fn get_hash_size(&self, algo: &Guid) -> Result<usize, Status> {
+		let mut rv = 0;
+		// SAFE: No memory unsafety
+		unsafe { (self.get_hash_size)(self, algo, &mut rv) }
+			.err_or(rv)
+	}
+
+	/// Hash a buffer in one call, for 32-byte digest algorithms (e.g. SHA-256)
+	///
+	/// Returns `INVALID_PARAMETER` if the algorithm's digest isn't 32 bytes
+	pub fn hash(&self, algo: &Guid, data: &[u8]) -> Result<[u8; 32], Status> {
+		self.check_size(algo)?;
+		let mut out = [0; 64];
+		// SAFE: Pointer and length are from a valid slice, output is large enough for any algorithm
+		unsafe { (self.hash)(self, algo, data.as_ptr(), data.len(), &mut out) }?;
+		Ok(truncate(&out))
+	}
+
+	/// Start an incremental hash (replacing any in-progress one on this instance)
+	pub fn hash_init(&self, algo: &Guid) -> Result<(), Status> {
+		self.check_size(algo)?;
+		// SAFE: No memory unsafety
+		unsafe { (self.hash_init)(self, algo) }
+			.err_or( () )
+	}
+	/// Add data to the hash started by `hash_init`
+	pub fn hash_update(&self, data: &[u8]) -> Result<(), Status> {
+		// SAFE: Pointer and length are from a valid slice
+		unsafe { (self.hash_update)(self, data.as_ptr(), data.len()) }
+			.err_or( () )
+	}
+	/// Finish the hash started by `hash_init`, returning the digest
+	pub fn hash_final(&self) -> Result<[u8; 32], Status> {
+		let mut out = [0; 64];
+		// SAFE: Output is large enough for any algorithm
+		unsafe { (self.hash_final)(self, &mut out) }?;
+		Ok(truncate(&out))
+	}
+
+	fn check_size(&self, algo: &Guid) -> Result<(), Status> {
+		if self.get_hash_size(algo)? != 32 {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		Ok( () )
+	}
+}
+
+fn truncate(out: &Hash2Output) -> [u8; 32] {
+	let mut rv = [0; 32];
+	rv.copy_from_slice(&out[..32]);
+	rv
+}
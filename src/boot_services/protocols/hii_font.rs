@@ -0,0 +1,115 @@
+///
+///
+///
+use {Status,Guid,Void};
+use boot_services::BootServices;
+use super::{BltPixel, GraphicsOutput};
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0xe9ca4775,0x8657,0x47fc,[0x97,0xe7,0x7e,0xd6,0x5a,0x08,0x43,0x24]);
+
+/// HII image output buffer (`EFI_IMAGE_OUTPUT`)
+#[repr(C)]
+pub struct ImageOutput
+{
+	pub width: u16,
+	pub height: u16,
+	/// Either a bitmap (`*mut BltPixel`) or a `GraphicsOutput` screen, depending on the call
+	pub image: ImageOutputData,
+}
+#[repr(C)]
+pub union ImageOutputData
+{
+	pub bitmap: *mut BltPixel,
+	pub screen: *const GraphicsOutput,
+}
+
+/// Access to the firmware's fonts through the Human Interface Infrastructure (HII)
+#[repr(C)]
+pub struct HiiFont
+{
+	pub string_to_image: efi_fcn!{ fn() -> Status },
+	pub string_id_to_image: efi_fcn!{ fn() -> Status },
+	pub get_glyph: efi_fcn!{ fn(&HiiFont, /*char:*/ u16, /*string_info:*/ *const Void, /*blt:*/ &mut *mut ImageOutput, /*baseline:*/ Option<&mut usize>) -> Status },
+	pub get_font_info: efi_fcn!{ fn() -> Status },
+}
+impl super::Protocol for HiiFont
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const HiiFont
+	}
+}
+
+impl HiiFont
+{
+	/// Render a single character using the system default font
+	///
+	/// Returns `INVALID_PARAMETER` for characters outside the Basic Multilingual Plane (HII is UCS-2). If the
+	/// font doesn't contain the character, the firmware's replacement glyph is returned.
+	pub fn get_glyph<'a>(&self, bs: &'a BootServices, ch: char) -> Result<GlyphBlt<'a>, Status> {
+		let ch = ch as u32;
+		if ch > 0xFFFF {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let mut blt = ::core::ptr::null_mut();
+		let mut baseline = 0;
+		// SAFE: Null string info selects the default font, output pointers are valid
+		match unsafe { (self.get_glyph)(self, ch as u16, ::core::ptr::null(), &mut blt, Some(&mut baseline)) }
+		{
+		::status::SUCCESS => {},
+		::status::WARN_UNKNOWN_GLYPH => {},
+		e => return Err(e),
+		}
+		assert!( !blt.is_null() );
+		Ok(GlyphBlt {
+			bs: bs,
+			blt: blt,
+			baseline: baseline,
+			})
+	}
+}
+
+/// Rendered glyph bitmap, allocated by the firmware and freed on drop
+pub struct GlyphBlt<'a>
+{
+	bs: &'a BootServices,
+	blt: *mut ImageOutput,
+	baseline: usize,
+}
+impl<'a> GlyphBlt<'a>
+{
+	/// Width of the glyph cell in pixels (this is also the advance to the next character)
+	pub fn width(&self) -> u32 {
+		// SAFE: Valid firmware-allocated pointer
+		unsafe { (*self.blt).width as u32 }
+	}
+	/// Height of the glyph cell in pixels
+	pub fn height(&self) -> u32 {
+		// SAFE: Valid firmware-allocated pointer
+		unsafe { (*self.blt).height as u32 }
+	}
+	/// Offset from the top of the cell to the text baseline, in pixels
+	pub fn baseline(&self) -> usize {
+		self.baseline
+	}
+	/// Glyph pixels (row-major, `width * height` entries), suitable for `GraphicsOutput::blt_to_video`
+	pub fn pixels(&self) -> &[BltPixel] {
+		// SAFE: `get_glyph` outputs a bitmap of `width * height` pixels
+		unsafe {
+			::core::slice::from_raw_parts((*self.blt).image.bitmap, self.width() as usize * self.height() as usize)
+		}
+	}
+}
+impl<'a> ::core::ops::Drop for GlyphBlt<'a>
+{
+	fn drop(&mut self) {
+		// SAFE: Both the bitmap and output structure are pool allocations owned by this object
+		unsafe {
+			self.bs.free_pool((*self.blt).image.bitmap);
+			self.bs.free_pool(self.blt);
+		}
+	}
+}
@@ -0,0 +1,49 @@
+///
+///
+///
+use {Status, Guid, Void};
+use super::DevicePath;
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x4006c0c1,0xfcb3,0x403e,[0x99,0x6d,0x4a,0x6c,0x87,0x24,0xe0,0x6d]);
+
+/// Load a file from a device path (without the boot-manager policy of `LoadFile`)
+#[repr(C)]
+pub struct LoadFile2
+{
+	/// `boot_policy` must be false. If `buffer` is null or `buffer_size` is too small, the required size
+	/// is stored and `BUFFER_TOO_SMALL` returned.
+	pub load_file: efi_fcn!{ fn(&LoadFile2, /*file_path:*/ &DevicePath, /*boot_policy:*/ bool, /*buffer_size:*/ &mut usize, /*buffer:*/ *mut Void) -> Status },
+}
+impl super::Protocol for LoadFile2
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const LoadFile2
+	}
+}
+
+impl LoadFile2
+{
+	/// Load the file into `buffer`, returning the number of bytes loaded
+	///
+	/// Returns `BUFFER_TOO_SMALL` if it doesn't fit (pass an empty buffer to query the size with `file_size`)
+	pub fn load(&self, file_path: &DevicePath, buffer: &mut [u8]) -> Result<usize, Status> {
+		let mut len = buffer.len();
+		// SAFE: Buffer is valid for `len` bytes
+		(unsafe { (self.load_file)(self, file_path, false, &mut len, buffer.as_mut_ptr() as *mut Void) })?;
+		Ok(len)
+	}
+	/// Query the size of the file
+	pub fn file_size(&self, file_path: &DevicePath) -> Result<usize, Status> {
+		let mut len = 0;
+		// SAFE: Null buffer with zero size is permitted (and only the size is returned)
+		match unsafe { (self.load_file)(self, file_path, false, &mut len, ::core::ptr::null_mut()) }
+		{
+		::status::SUCCESS | ::status::BUFFER_TOO_SMALL => Ok(len),
+		v => Err(v),
+		}
+	}
+}
@@ -28,10 +28,34 @@ pub struct LoadedImage<'a>
 	pub image_code_type: MemoryType,
 	pub image_data_type: MemoryType,
 	
-	pub unload: extern "win64" fn(Handle) -> Status,
+	/// Unload callback (null if the image can't be unloaded), see `BootServices::register_image_unload`
+	pub unload: Option<ImageUnloadFcn>,
 }
 
 
+impl<'a> LoadedImage<'a>
+{
+	/// Set the load options (command line) that the image will see, e.g. before `start_image` when chainloading
+	///
+	/// `opts` is usually a NUL-terminated UCS-2 string, as expected by the Linux EFI stub and the shell.
+	/// Only the pointer is stored, so the buffer must outlive `start_image` (and whatever the image does with
+	/// its options afterwards - for a kernel that never returns, it must not be freed at all).
+	///
+	/// Takes `&self` because the protocol is only reachable through `handle_protocol`, which returns a shared
+	/// reference. The fields are written through a raw pointer, as `BootServices::register_image_unload` does.
+	///
+	/// UNSAFE: `opts` must remain valid for as long as the image may read its load options, and the image
+	/// mustn't be running (the options are only read by the image itself, e.g. after `start_image`)
+	pub unsafe fn set_load_options(&self, opts: &[u16]) {
+		let this = self as *const LoadedImage as *mut LoadedImage;
+		(*this).load_options = opts.as_ptr() as *mut Void;
+		(*this).load_options_size = (opts.len() * 2) as u32;
+	}
+}
+
+/// Image unload callback, passed the image's own handle
+pub type ImageUnloadFcn = extern "win64" fn(Handle) -> Status;
+
 impl<'a> super::Protocol for LoadedImage<'a>
 {
 	fn guid() -> Guid {
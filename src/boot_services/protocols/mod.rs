@@ -2,11 +2,22 @@
 //
 /// Various object protocols
 
-pub use self::loaded_image::LoadedImage;
+pub use self::loaded_image::{LoadedImage, ImageUnloadFcn};
 pub use self::loaded_image_device_path::LoadedImageDevicePath;
-pub use self::device_path::DevicePath;
-pub use self::simple_file_system::SimpleFileSystem;
+pub use self::device_path::{DevicePath, Nodes};
+pub use self::device_path_utilities::DevicePathUtilities;
+pub use self::device_path_from_text::DevicePathFromText;
+pub use self::simple_file_system::{SimpleFileSystem, Volume};
 pub use self::graphics_output::{GraphicsOutput, PixelFormat, BltOperation, BltPixel, ModeInformation};
+pub use self::graphics_output::{Mode, PixelBitmask, FramebufferInfo};
+pub use self::graphics_output::{encode_pixel, convert_pixels};
+pub use self::pci_io::{PciIo, PciIoWidth};
+pub use self::shell::{Shell, ShellFileHandle};
+pub use self::shell_parameters::ShellParameters;
+pub use self::hii_font::{HiiFont, GlyphBlt};
+pub use self::hash2::{Hash2, Hash2Output};
+pub use self::load_file2::LoadFile2;
+pub use self::hash2::{HASH_ALGORITHM_SHA1_GUID, HASH_ALGORITHM_SHA256_GUID, HASH_ALGORITHM_SHA384_GUID, HASH_ALGORITHM_SHA512_GUID};
 
 pub use self::file::*;
 
@@ -19,8 +30,16 @@ pub trait Protocol
 mod loaded_image;
 mod loaded_image_device_path;
 mod device_path;
+mod device_path_utilities;
+mod device_path_from_text;
 mod simple_file_system;
 
 mod graphics_output;
+mod hii_font;
+mod hash2;
+mod load_file2;
+mod pci_io;
+mod shell;
+mod shell_parameters;
 pub mod file;
 
@@ -0,0 +1,123 @@
+///
+///
+///
+use {Status,Guid,Void};
+use boot_services::{AllocateType, MemoryType};
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x4cf5b200,0x68b8,0x4ca5,[0x9e,0xec,0xb2,0x3e,0x3f,0x50,0x02,0x9a]);
+
+/// Access width (and addressing mode) for PCI IO operations
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+pub enum PciIoWidth
+{
+	Uint8,
+	Uint16,
+	Uint32,
+	Uint64,
+	/// Repeated access to the same address
+	FifoUint8,
+	FifoUint16,
+	FifoUint32,
+	FifoUint64,
+	/// Same value written to consecutive addresses
+	FillUint8,
+	FillUint16,
+	FillUint32,
+	FillUint64,
+	Maximum,
+}
+
+#[repr(C)]
+pub struct PciIoAccess
+{
+	pub read: efi_fcn!{ fn(&PciIo, PciIoWidth, /*bar_index:*/ u8, /*offset:*/ u64, /*count:*/ usize, *mut Void) -> Status },
+	pub write: efi_fcn!{ fn(&PciIo, PciIoWidth, /*bar_index:*/ u8, /*offset:*/ u64, /*count:*/ usize, *const Void) -> Status },
+}
+#[repr(C)]
+pub struct PciIoConfigAccess
+{
+	pub read: efi_fcn!{ fn(&PciIo, PciIoWidth, /*offset:*/ u32, /*count:*/ usize, *mut Void) -> Status },
+	pub write: efi_fcn!{ fn(&PciIo, PciIoWidth, /*offset:*/ u32, /*count:*/ usize, *const Void) -> Status },
+}
+
+#[repr(C)]
+pub struct PciIo
+{
+	pub poll_mem: efi_fcn!{ fn(&PciIo, PciIoWidth, u8, u64, /*mask:*/ u64, /*value:*/ u64, /*delay:*/ u64, &mut u64) -> Status },
+	pub poll_io: efi_fcn!{ fn(&PciIo, PciIoWidth, u8, u64, /*mask:*/ u64, /*value:*/ u64, /*delay:*/ u64, &mut u64) -> Status },
+	pub mem: PciIoAccess,
+	pub io: PciIoAccess,
+	pub pci: PciIoConfigAccess,
+	pub copy_mem: efi_fcn!{ fn(&PciIo, PciIoWidth, /*dest_bar:*/ u8, /*dest_offset:*/ u64, /*src_bar:*/ u8, /*src_offset:*/ u64, /*count:*/ usize) -> Status },
+	pub map: efi_fcn!{ fn(&PciIo, /*operation:*/ u32, /*host_address:*/ *mut Void, /*number_of_bytes:*/ &mut usize, /*device_address:*/ &mut ::PhysicalAddress, /*mapping:*/ &mut *mut Void) -> Status },
+	pub unmap: efi_fcn!{ fn(&PciIo, /*mapping:*/ *mut Void) -> Status },
+	pub allocate_buffer: efi_fcn!{ fn(&PciIo, AllocateType, MemoryType, /*pages:*/ usize, /*host_address:*/ &mut *mut Void, /*attributes:*/ u64) -> Status },
+	pub free_buffer: efi_fcn!{ fn(&PciIo, /*pages:*/ usize, /*host_address:*/ *mut Void) -> Status },
+	pub flush: efi_fcn!{ fn(&PciIo) -> Status },
+	pub get_location: efi_fcn!{ fn(&PciIo, /*segment:*/ &mut usize, /*bus:*/ &mut usize, /*device:*/ &mut usize, /*function:*/ &mut usize) -> Status },
+	pub attributes: efi_fcn!{ fn(&PciIo, /*operation:*/ u32, /*attributes:*/ u64, /*result:*/ Option<&mut u64>) -> Status },
+	pub get_bar_attributes: efi_fcn!{ fn(&PciIo, /*bar_index:*/ u8, /*supports:*/ Option<&mut u64>, /*resources:*/ Option<&mut *mut Void>) -> Status },
+	pub set_bar_attributes: efi_fcn!{ fn(&PciIo, /*attributes:*/ u64, /*bar_index:*/ u8, /*offset:*/ &mut u64, /*length:*/ &mut u64) -> Status },
+	pub rom_size: u64,
+	pub rom_image: *mut Void,
+}
+impl super::Protocol for PciIo
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const PciIo
+	}
+}
+
+macro_rules! def_access {
+	($($t:ty, $w:ident => $cfg_read:ident, $cfg_write:ident, $mem_read:ident, $mem_write:ident;)*) => {
+		$(
+		/// Read from PCI configuration space
+		pub fn $cfg_read(&self, offset: u32) -> Result<$t, Status> {
+			let mut v: $t = 0;
+			// SAFE: Single value read into a valid location
+			unsafe { (self.pci.read)(self, PciIoWidth::$w, offset, 1, &mut v as *mut $t as *mut Void) }
+				.err_or(v)
+		}
+		/// Write to PCI configuration space
+		pub fn $cfg_write(&self, offset: u32, value: $t) -> Result<(), Status> {
+			// SAFE: Single value written from a valid location
+			unsafe { (self.pci.write)(self, PciIoWidth::$w, offset, 1, &value as *const $t as *const Void) }
+				.err_or( () )
+		}
+		/// Read from a memory BAR (`offset` is relative to the start of the BAR)
+		pub fn $mem_read(&self, bar: u8, offset: u64) -> Result<$t, Status> {
+			let mut v: $t = 0;
+			// SAFE: Single value read into a valid location (the firmware validates the BAR range)
+			unsafe { (self.mem.read)(self, PciIoWidth::$w, bar, offset, 1, &mut v as *mut $t as *mut Void) }
+				.err_or(v)
+		}
+		/// Write to a memory BAR (`offset` is relative to the start of the BAR)
+		pub fn $mem_write(&self, bar: u8, offset: u64, value: $t) -> Result<(), Status> {
+			// SAFE: Single value written from a valid location (the firmware validates the BAR range)
+			unsafe { (self.mem.write)(self, PciIoWidth::$w, bar, offset, 1, &value as *const $t as *const Void) }
+				.err_or( () )
+		}
+		)*
+	}
+}
+impl PciIo
+{
+	def_access!{
+		u8 , Uint8  => config_read8 , config_write8 , mem_read8 , mem_write8 ;
+		u16, Uint16 => config_read16, config_write16, mem_read16, mem_write16;
+		u32, Uint32 => config_read32, config_write32, mem_read32, mem_write32;
+	}
+
+	/// Obtain the PCI address of the device as (segment, bus, device, function)
+	pub fn get_location(&self) -> Result<(u32,u32,u32,u32), Status> {
+		let (mut seg, mut bus, mut dev, mut fcn) = (0, 0, 0, 0);
+		// SAFE: No memory unsafety
+		unsafe { (self.get_location)(self, &mut seg, &mut bus, &mut dev, &mut fcn) }
+			.err_or( (seg as u32, bus as u32, dev as u32, fcn as u32) )
+	}
+}
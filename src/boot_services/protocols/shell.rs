@@ -0,0 +1,125 @@
+///
+///
+///
+use {Status,Guid,Void,CStr16,CStr16Ptr};
+use boot_services::{BootServices, raw};
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x6302d008,0x7f9b,0x4f30,[0x87,0xac,0x60,0xc9,0xfe,0xf5,0xda,0x4e]);
+
+/// Shell-managed file handle (distinct from `File`, only usable with the shell protocol)
+pub type ShellFileHandle = *mut Void;
+
+/// UEFI Shell protocol (only present when launched from the UEFI Shell)
+#[repr(C)]
+pub struct Shell
+{
+	pub execute: efi_fcn!{ fn() -> Status },
+	pub get_env: efi_fcn!{ fn(/*name:*/ CStr16Ptr) -> CStr16Ptr },
+	pub set_env: efi_fcn!{ fn() -> Status },
+	pub get_alias: efi_fcn!{ fn() -> Status },
+	pub set_alias: efi_fcn!{ fn() -> Status },
+	pub get_help_text: efi_fcn!{ fn() -> Status },
+	pub get_device_path_from_map: efi_fcn!{ fn() -> Status },
+	pub get_map_from_device_path: efi_fcn!{ fn() -> Status },
+	pub get_device_path_from_file_path: efi_fcn!{ fn() -> Status },
+	pub get_file_path_from_device_path: efi_fcn!{ fn() -> Status },
+	pub set_map: efi_fcn!{ fn() -> Status },
+	pub get_cur_dir: efi_fcn!{ fn(/*file_system_mapping:*/ CStr16Ptr) -> CStr16Ptr },
+	pub set_cur_dir: efi_fcn!{ fn() -> Status },
+	pub open_file_list: efi_fcn!{ fn() -> Status },
+	pub free_file_list: efi_fcn!{ fn() -> Status },
+	pub remove_dup_in_file_list: efi_fcn!{ fn() -> Status },
+	pub batch_is_active: efi_fcn!{ fn() -> bool },
+	pub is_root_shell: efi_fcn!{ fn() -> bool },
+	pub enable_page_break: efi_fcn!{ fn() -> () },
+	pub disable_page_break: efi_fcn!{ fn() -> () },
+	pub get_page_break: efi_fcn!{ fn() -> bool },
+	pub get_device_name: efi_fcn!{ fn() -> Status },
+	pub get_file_info: efi_fcn!{ fn() -> Status },
+	pub set_file_info: efi_fcn!{ fn() -> Status },
+	pub open_file_by_name: efi_fcn!{ fn(/*file_name:*/ CStr16Ptr, &mut ShellFileHandle, /*open_mode:*/ u64) -> Status },
+	pub close_file: efi_fcn!{ fn(ShellFileHandle) -> Status },
+	pub create_file: efi_fcn!{ fn() -> Status },
+	pub read_file: efi_fcn!{ fn() -> Status },
+	pub write_file: efi_fcn!{ fn() -> Status },
+	pub delete_file: efi_fcn!{ fn() -> Status },
+	pub delete_file_by_name: efi_fcn!{ fn() -> Status },
+	pub get_file_position: efi_fcn!{ fn() -> Status },
+	pub set_file_position: efi_fcn!{ fn() -> Status },
+	pub flush_file: efi_fcn!{ fn() -> Status },
+	pub find_files: efi_fcn!{ fn() -> Status },
+	pub find_files_in_dir: efi_fcn!{ fn() -> Status },
+	pub get_file_size: efi_fcn!{ fn() -> Status },
+	pub open_root: efi_fcn!{ fn() -> Status },
+	pub open_root_by_handle: efi_fcn!{ fn() -> Status },
+	pub execution_break: raw::Event,
+	pub major_version: u32,
+	pub minor_version: u32,
+}
+impl super::Protocol for Shell
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const Shell
+	}
+}
+
+impl Shell
+{
+	/// Locate the shell protocol, returns `UNSUPPORTED` if the application wasn't launched from the shell
+	pub fn new(bs: &BootServices) -> Result<&'static Shell, Status> {
+		bs.locate_protocol::<Shell>()
+			.map_err(|_| ::status::UNSUPPORTED)
+	}
+
+	/// Get the value of a shell environment variable
+	pub fn get_env(&self, name: &CStr16) -> Option<&CStr16> {
+		// SAFE: NUL terminated valid pointer
+		let p = unsafe { (self.get_env)(name.as_ptr()) };
+		if p.is_null() {
+			None
+		}
+		else {
+			// SAFE: (assumed) Non-null value from the shell is NUL terminated, and remains valid until changed
+			Some( unsafe { CStr16::from_nul_terminated(p) } )
+		}
+	}
+
+	/// Get the current directory, for the specified mapping (e.g. `fs0:`) or the current mapping if `None`
+	pub fn get_cur_dir(&self, mapping: Option<&CStr16>) -> Option<&CStr16> {
+		let mapping = match mapping
+			{
+			Some(m) => m.as_ptr(),
+			None => ::core::ptr::null(),
+			};
+		// SAFE: Null or NUL terminated valid pointer
+		let p = unsafe { (self.get_cur_dir)(mapping) };
+		if p.is_null() {
+			None
+		}
+		else {
+			// SAFE: (assumed) Non-null value from the shell is NUL terminated
+			Some( unsafe { CStr16::from_nul_terminated(p) } )
+		}
+	}
+
+	/// Open a file by its shell path (relative to the current directory, or including a mapping)
+	///
+	/// `mode` is a combination of the `FILE_MODE_*` flags. Close the handle with `close_file`.
+	pub fn open_file_by_name(&self, name: &CStr16, mode: u64) -> Result<ShellFileHandle, Status> {
+		let mut h = ::core::ptr::null_mut();
+		// SAFE: NUL terminated valid pointer
+		unsafe { (self.open_file_by_name)(name.as_ptr(), &mut h, mode) }
+			.err_or(h)
+	}
+	/// Close a handle returned by `open_file_by_name`
+	///
+	/// UNSAFE: `handle` must be an open handle from `open_file_by_name`, and not be used again afterwards
+	pub unsafe fn close_file(&self, handle: ShellFileHandle) -> Result<(), Status> {
+		(self.close_file)(handle)
+			.err_or( () )
+	}
+}
@@ -0,0 +1,62 @@
+///
+///
+///
+use {Guid,Void,CStr16,CStr16Ptr};
+use super::ShellFileHandle;
+
+/// Protocol GUID
+pub const GUID: Guid = Guid(0x752f3136,0x4e16,0x4fdc,[0xa2,0x2a,0xe5,0xf4,0x68,0x12,0xf4,0xca]);
+
+/// Command-line arguments and standard IO handles for an application launched from the UEFI Shell
+///
+/// Installed by the shell on the application's image handle (use `BootServices::handle_protocol`). If the
+/// application was started directly by the firmware (e.g. a boot option) this protocol is absent, and the
+/// command line is only available as the raw `LoadedImage::load_options` buffer (see `::args::parse_load_options`).
+#[repr(C)]
+pub struct ShellParameters
+{
+	argv: *const CStr16Ptr,
+	argc: usize,
+	pub stdin: ShellFileHandle,
+	pub stdout: ShellFileHandle,
+	pub stderr: ShellFileHandle,
+}
+impl super::Protocol for ShellParameters
+{
+	fn guid() -> Guid {
+		GUID
+	}
+	unsafe fn from_ptr(ptr: *const Void) -> *const Self {
+		ptr as *const ShellParameters
+	}
+}
+
+impl ShellParameters
+{
+	/// Number of arguments (including the application name as the first)
+	#[inline]
+	pub fn argc(&self) -> usize {
+		self.argc
+	}
+	/// Iterate over the arguments (the first is the application name)
+	pub fn args(&self) -> Args {
+		Args(self, 0)
+	}
+}
+
+pub struct Args<'a>(&'a ShellParameters, usize);
+impl<'a> Iterator for Args<'a>
+{
+	type Item = &'a CStr16;
+	fn next(&mut self) -> Option<&'a CStr16> {
+		if self.1 == self.0.argc {
+			None
+		}
+		else {
+			// SAFE: (assumed) The shell provides `argc` valid NUL terminated pointers
+			let rv = unsafe { CStr16::from_nul_terminated(*self.0.argv.offset(self.1 as isize)) };
+			self.1 += 1;
+			Some(rv)
+		}
+	}
+}
@@ -1,4 +1,6 @@
 use {Status, FILE_SYSTEM_GUID, Guid};
+use boot_services::{BootServices, Owned};
+use super::{File, FILE_MODE_READ};
 
 #[repr(C)]
 pub struct SimpleFileSystem
@@ -16,3 +18,122 @@ impl super::Protocol for SimpleFileSystem
 		v as *const _
 	}
 }
+
+impl SimpleFileSystem
+{
+	/// Protocol revision defined by the specification (EFI 1.10 onwards)
+	pub const REVISION: u64 = 0x0001_0000;
+	/// Revision reported by some pre-1.10 firmware, which has subtly different `File` semantics
+	pub const REVISION_LEGACY: u64 = 1;
+
+	/// Protocol revision (`Self::REVISION`, or `Self::REVISION_LEGACY` on some very old firmware)
+	#[inline]
+	pub fn revision(&self) -> u64 {
+		self.revision
+	}
+	/// Returns true if this is a pre-1.10 implementation that may need special handling
+	#[inline]
+	pub fn is_legacy_revision(&self) -> bool {
+		self.revision < Self::REVISION
+	}
+
+	/// Open the root directory of the volume
+	///
+	/// Returns `UNSUPPORTED` without calling the firmware if the revision isn't one this crate understands
+	/// (zero, or a major version above 1). Errors from the firmware:
+	/// - `UNSUPPORTED`: the volume's file system isn't supported
+	/// - `NO_MEDIA`: there's no medium in the device
+	/// - `DEVICE_ERROR`/`VOLUME_CORRUPTED`: the device or file system is faulty
+	/// - `MEDIA_CHANGED`: the medium was swapped, and the protocol instance has been (or will be) reinstalled
+	///
+	/// NOTE: After `MEDIA_CHANGED` (e.g. a USB stick or CD being replaced), this instance is stale. Look the
+	/// protocol up again (e.g. with `all`) before retrying, and expect handles from the old medium to fail.
+	pub fn open_volume<'a>(&self, bs: &'a BootServices) -> Result<Owned<'a, super::File>, Status> {
+		if self.revision == 0 || self.revision >> 16 > 1 {
+			return Err(::status::UNSUPPORTED);
+		}
+		let mut root = ::core::ptr::null_mut();
+		// SAFE: Output pointer is valid
+		(unsafe { (self.open_volume)(self, &mut root) })?;
+		// SAFE: A successful open returns a new handle, which is now owned
+		Ok( unsafe { Owned::from_ptr(bs, root) } )
+	}
+
+	/// Open the root directory and wrap it in a `Volume`, for repeated path-based access
+	pub fn mount<'a>(&self, bs: &'a BootServices) -> Result<Volume<'a>, Status> {
+		Ok(Volume {
+			root: self.open_volume(bs)?,
+			})
+	}
+
+	/// Iterate over every simple file system (volume) present on the machine
+	///
+	/// ```no_run
+	/// # fn f(bs: &::uefi::boot_services::BootServices) {
+	/// for fs in ::uefi::boot_services::protocols::SimpleFileSystem::all(bs) {
+	/// 	if let Ok(root) = fs.open_volume(bs) {
+	/// 		// ...
+	/// 	}
+	/// }
+	/// # }
+	/// ```
+	pub fn all(bs: &BootServices) -> impl Iterator<Item=&SimpleFileSystem> {
+		bs.all_protocols::<SimpleFileSystem>()
+	}
+}
+
+/// Mounted volume (see `SimpleFileSystem::mount`), holding the root directory open
+///
+/// Paths are relative to the root, in `File::open_path` form (e.g. `"EFI\\BOOT\\BOOTX64.EFI"`). The root
+/// handle is closed when the `Volume` is dropped, files opened through it have their own handles and
+/// can outlive it.
+pub struct Volume<'a>
+{
+	root: Owned<'a, File>,
+}
+impl<'a> Volume<'a>
+{
+	/// Root directory of the volume (e.g. to open in other modes, or to list it)
+	pub fn root(&self) -> &File {
+		&self.root
+	}
+
+	/// Open a file or directory read-only
+	pub fn open(&self, path: &str) -> Result<Owned<'a, File>, Status> {
+		self.root.open_path(self.root.boot_services(), path, FILE_MODE_READ)
+	}
+
+	/// Read a whole file into `buf`, returning its length
+	///
+	/// Returns `BUFFER_TOO_SMALL` (without reading) if the file is larger than `buf`.
+	pub fn read_file(&self, path: &str, buf: &mut [u8]) -> Result<usize, Status> {
+		let mut file = self.open(path)?;
+		let size = file.info()?.FileSize;
+		if size > buf.len() as u64 {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		let size = size as usize;
+		let mut ofs = 0;
+		while ofs < size
+		{
+			match file.read(&mut buf[ofs .. size])?
+			{
+			0 => break,
+			n => ofs += n,
+			}
+		}
+		Ok(ofs)
+	}
+
+	/// Check if a file or directory exists
+	///
+	/// Errors other than `NOT_FOUND` (e.g. `DEVICE_ERROR`) are returned rather than treated as absence.
+	pub fn exists(&self, path: &str) -> Result<bool, Status> {
+		match self.open(path)
+		{
+		Ok(_) => Ok(true),
+		Err(::status::NOT_FOUND) => Ok(false),
+		Err(e) => Err(e),
+		}
+	}
+}
@@ -0,0 +1,55 @@
+//! Periodic tick source, e.g. for animating a boot menu
+use super::{BootServices, Event, TimerDelay, EVT_TIMER, TPL_APPLICATION};
+use Status;
+
+/// Periodic timer that counts elapsed ticks without busy-waiting
+///
+/// UEFI timers are specified in units of 100ns, this converts from milliseconds
+pub struct Ticker<'a>
+{
+	bs: &'a BootServices,
+	event: Event,
+	ticks: u64,
+}
+impl<'a> Ticker<'a>
+{
+	/// Create a ticker that fires every `period_ms` milliseconds
+	pub fn new(bs: &'a BootServices, period_ms: u64) -> Result<Ticker<'a>, Status> {
+		let event = bs.create_event(EVT_TIMER, TPL_APPLICATION, None)?;
+		// 1ms = 10,000 100ns units
+		if let Err(e) = bs.set_timer(&event, TimerDelay::Periodic, period_ms * 10_000) {
+			bs.close_event(event);
+			return Err(e);
+		}
+		Ok(Ticker {
+			bs: bs,
+			event: event,
+			ticks: 0,
+			})
+	}
+
+	/// Number of ticks observed so far
+	///
+	/// The firmware only records whether the timer has fired since the last check, so if this is polled
+	/// less often than the period, the missed ticks are merged into one.
+	pub fn elapsed_ticks(&mut self) -> u64 {
+		if let Ok(true) = self.bs.check_event(&self.event) {
+			self.ticks += 1;
+		}
+		self.ticks
+	}
+
+	/// Block until the next tick, returning the new tick count
+	pub fn wait(&mut self) -> Result<u64, Status> {
+		self.bs.wait_for_single_event(self.event)?;
+		self.ticks += 1;
+		Ok(self.ticks)
+	}
+}
+impl<'a> Drop for Ticker<'a>
+{
+	fn drop(&mut self) {
+		let _ = self.bs.set_timer(&self.event, TimerDelay::Cancel, 0);
+		self.bs.close_event(self.event);
+	}
+}
@@ -37,6 +37,97 @@ impl<'a> Drop for EfiLogger<'a> {
 	}
 }
 
+/// Log level, used by `log_info!`/`log_warn!`/`log_error!`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LogLevel
+{
+	Info,
+	Warn,
+	Error,
+}
+impl LogLevel
+{
+	/// Tag printed before the message
+	pub fn tag(&self) -> &'static str {
+		match *self
+		{
+		LogLevel::Info => "INFO",
+		LogLevel::Warn => "WARN",
+		LogLevel::Error => "ERROR",
+		}
+	}
+	/// Foreground colour of the tag
+	pub fn color(&self) -> Color {
+		match *self
+		{
+		LogLevel::Info => Color::LightGreen,
+		LogLevel::Warn => Color::Yellow,
+		LogLevel::Error => Color::LightRed,
+		}
+	}
+}
+
+/// Write a `[LEVEL] [module] message` line (the backend of the `log_*!` macros)
+///
+/// Only the tag is coloured, with the background and the previous attribute kept. The attribute is a
+/// console feature: sinks that aren't an interactive console (e.g. serial redirection, or a log captured
+/// to a file) may ignore it or translate it into escape codes.
+pub fn log_at(out: &SimpleTextOutputInterface, level: LogLevel, module: &str, args: ::core::fmt::Arguments) {
+	use core::fmt::Write;
+	let prev = out.mode.attribute as usize;
+	out.output_string_utf8("[");
+	out.set_attribute((prev & !0xF) | level.color() as usize);
+	out.output_string_utf8(level.tag());
+	out.set_attribute(prev);
+	let mut logger = EfiLogger::new(out);
+	let _ = write!(&mut logger, "] [{}] ", module);
+	let _ = logger.write_fmt(args);
+}
+
+/// Text console colour
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Color
+{
+	Black = 0x0,
+	Blue = 0x1,
+	Green = 0x2,
+	Cyan = 0x3,
+	Red = 0x4,
+	Magenta = 0x5,
+	Brown = 0x6,
+	LightGray = 0x7,
+	DarkGray = 0x8,
+	LightBlue = 0x9,
+	LightGreen = 0xA,
+	LightCyan = 0xB,
+	LightRed = 0xC,
+	LightMagenta = 0xD,
+	Yellow = 0xE,
+	White = 0xF,
+}
+impl Color
+{
+	/// Combine into a `set_attribute` value
+	///
+	/// Only the first eight colours are valid backgrounds, brighter ones map to their darker equivalent
+	pub fn attribute(fg: Color, bg: Color) -> usize {
+		fg as usize | (bg as usize & 0x7) << 4
+	}
+}
+
+/// Restores the previous text attribute when dropped (see `SimpleTextOutputInterface::with_attribute`)
+pub struct AttributeGuard<'a>
+{
+	out: &'a SimpleTextOutputInterface,
+	prev: usize,
+}
+impl<'a> Drop for AttributeGuard<'a>
+{
+	fn drop(&mut self) {
+		self.out.set_attribute(self.prev);
+	}
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct SimpleTextOutputMode {
@@ -104,6 +195,19 @@ impl SimpleTextOutputInterface
 		} 
 	}
 
+	/// Set the foreground and background colours until the returned guard is dropped
+	///
+	/// The previous attribute (from `mode.attribute`) is restored on drop, so guards can be nested (as long
+	/// as they're dropped in reverse order, which scoping ensures).
+	pub fn with_attribute<'a>(&'a self, fg: Color, bg: Color) -> AttributeGuard<'a> {
+		let prev = self.mode.attribute as usize;
+		self.set_attribute(Color::attribute(fg, bg));
+		AttributeGuard {
+			out: self,
+			prev: prev,
+			}
+	}
+
 	#[inline]
 	pub fn clear_screen(&self) -> Status {
 		unsafe {
@@ -118,6 +222,14 @@ impl SimpleTextOutputInterface
 		}
 	}
 
+	/// Current cursor position as (column, row), for saving and later restoring with `set_cursor_position`
+	///
+	/// Only meaningful in text mode (consoles without a cursor, e.g. some serial redirection, may not update it)
+	#[inline]
+	pub fn cursor_position(&self) -> (usize, usize) {
+		(self.mode.cursor_column as usize, self.mode.cursor_row as usize)
+	}
+
 	#[inline]
 	pub fn enable_cursor(&self, visible: bool) -> Status {
 		unsafe {
@@ -125,6 +237,16 @@ impl SimpleTextOutputInterface
 		}
 	}
 
+	/// Ring the console bell (outputs a single BEL code unit)
+	///
+	/// Best-effort, as not all firmware consoles honour BEL (some may ignore it or print a glyph).
+	pub fn beep(&self) {
+		// SAFE: NUL terminated valid pointer, passed directly to the firmware (no translation)
+		unsafe {
+			self.output_string( [0x07, 0].as_ptr() );
+		}
+	}
+
 	/// Helper - Print the passed rust string to the console (does multiple calls to `output_string`)
 	pub fn output_string_utf8(&self, s: &str) -> Status {
 		for c in s.chars() {
@@ -148,6 +270,70 @@ pub struct InputKey
 	pub scan_code: u16,
 	pub unicode_char: u16,
 }
+impl InputKey
+{
+	/// Enter/Return key (carriage return, or line feed on some consoles)
+	#[inline]
+	pub fn is_enter(&self) -> bool {
+		self.unicode_char == keys::CHAR_CARRIAGE_RETURN || self.unicode_char == keys::CHAR_LINEFEED
+	}
+	#[inline]
+	pub fn is_escape(&self) -> bool {
+		self.scan_code == scan_codes::ESC
+	}
+	/// One of the four arrow keys
+	#[inline]
+	pub fn is_arrow(&self) -> bool {
+		match self.scan_code
+		{
+		scan_codes::UP | scan_codes::DOWN | scan_codes::RIGHT | scan_codes::LEFT => true,
+		_ => false,
+		}
+	}
+}
+
+/// `InputKey::scan_code` values for non-printable keys
+///
+/// From the "EFI Scan Codes for EFI_SIMPLE_TEXT_INPUT_PROTOCOL" table (UEFI spec, Simple Text Input Protocol)
+pub mod scan_codes
+{
+	pub const NULL: u16 = 0x00;
+	pub const UP: u16 = 0x01;
+	pub const DOWN: u16 = 0x02;
+	pub const RIGHT: u16 = 0x03;
+	pub const LEFT: u16 = 0x04;
+	pub const HOME: u16 = 0x05;
+	pub const END: u16 = 0x06;
+	pub const INSERT: u16 = 0x07;
+	pub const DELETE: u16 = 0x08;
+	pub const PAGE_UP: u16 = 0x09;
+	pub const PAGE_DOWN: u16 = 0x0A;
+	pub const F1: u16 = 0x0B;
+	pub const F2: u16 = 0x0C;
+	pub const F3: u16 = 0x0D;
+	pub const F4: u16 = 0x0E;
+	pub const F5: u16 = 0x0F;
+	pub const F6: u16 = 0x10;
+	pub const F7: u16 = 0x11;
+	pub const F8: u16 = 0x12;
+	pub const F9: u16 = 0x13;
+	pub const F10: u16 = 0x14;
+	pub const F11: u16 = 0x15;
+	pub const F12: u16 = 0x16;
+	pub const ESC: u16 = 0x17;
+}
+
+/// `InputKey::unicode_char` values for control characters
+///
+/// From the "EFI Cursor Location/Unicode Control Characters" table (UEFI spec, Simple Text Input Protocol)
+pub mod keys
+{
+	pub const CHAR_NULL: u16 = 0x0000;
+	pub const CHAR_BACKSPACE: u16 = 0x0008;
+	pub const CHAR_TAB: u16 = 0x0009;
+	pub const CHAR_LINEFEED: u16 = 0x000A;
+	pub const CHAR_CARRIAGE_RETURN: u16 = 0x000D;
+}
 
 #[repr(C)]
 pub struct SimpleInputInterface
@@ -164,11 +350,60 @@ impl SimpleInputInterface
 		(self.reset)(self, false)
 	}
 
+	/// Read a pending keystroke, returns `NOT_READY` if there isn't one
 	#[inline]
-	pub fn read_key_stroke(&mut self) -> Result<InputKey, Status> {
+	pub fn read_key_stroke(&self) -> Result<InputKey, Status> {
 		let mut ik = Default::default();
-		let s = (self.read_key_stroke)(self, &mut ik);
+		// NOTE: The firmware takes a mutable pointer, but this doesn't mutate the interface structure
+		let s = (self.read_key_stroke)(self as *const _ as *mut _, &mut ik);
 		s.err_or(ik)
 	}
 }
 
+/// Read a line of input, echoing it to `output`
+///
+/// Printable characters are echoed, backspace erases the last character, and Enter finishes the line.
+/// Characters typed once `buf` is full are ignored (and the console bell is rung). The returned string is stored in `buf` (and is NUL terminated).
+///
+/// NOTE: This polls `read_key_stroke` until a key is available.
+pub fn read_line<'a>(input: &SimpleInputInterface, output: &SimpleTextOutputInterface, buf: &'a mut [u16]) -> Result<&'a super::CStr16, Status>
+{
+	if buf.len() == 0 {
+		return Err(status::BUFFER_TOO_SMALL);
+	}
+	let mut len = 0;
+	loop
+	{
+		let key = match input.read_key_stroke()
+			{
+			Ok(k) => k,
+			Err(status::NOT_READY) => continue,
+			Err(e) => return Err(e),
+			};
+		if key.is_enter() {
+			break;
+		}
+		match key.unicode_char
+		{
+		keys::CHAR_BACKSPACE => if len > 0 {
+			len -= 1;
+			// SAFE: NUL terminated valid pointer
+			unsafe { output.output_string([keys::CHAR_BACKSPACE, b' ' as u16, keys::CHAR_BACKSPACE, 0].as_ptr()); }
+			},
+		c if c >= 0x20 => if len + 1 < buf.len() {
+			buf[len] = c;
+			len += 1;
+			// SAFE: NUL terminated valid pointer
+			unsafe { output.output_string([c, 0].as_ptr()); }
+			}
+			else {
+				output.beep();
+			},
+		_ => {},
+		}
+	}
+	output.output_string_utf8("\r\n");
+	buf[len] = 0;
+	Ok( super::CStr16::from_slice(&buf[.. len + 1]) )
+}
+
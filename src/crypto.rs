@@ -0,0 +1,187 @@
+//! Software checksums and cryptographic primitives
+//!
+//! Fallbacks for when the firmware doesn't provide `Hash2` or `calculate_crc32` (prefer those where available)
+
+/// CRC-32 (IEEE 802.3, as used by UEFI table headers and GPT) of a buffer
+///
+/// Equivalent to `BootServices::calculate_crc32`, for use when that isn't available
+pub fn crc32(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &b in data
+	{
+		crc ^= b as u32;
+		for _ in 0 .. 8
+		{
+			crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+/// SHA-256 round constants
+const K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+	];
+/// Initial hash value
+const H0: [u32; 8] = [
+	0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+	];
+
+/// Hash a buffer with SHA-256
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+	let mut h = Sha256::new();
+	h.update(data);
+	h.finish()
+}
+
+/// Incremental SHA-256 state
+#[derive(Clone)]
+pub struct Sha256
+{
+	state: [u32; 8],
+	/// Partial block
+	buf: [u8; 64],
+	buf_len: usize,
+	/// Total message length in bytes
+	total_len: u64,
+}
+impl Sha256
+{
+	pub fn new() -> Sha256 {
+		Sha256 {
+			state: H0,
+			buf: [0; 64],
+			buf_len: 0,
+			total_len: 0,
+			}
+	}
+
+	/// Add data to the hash
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.total_len = self.total_len.wrapping_add(data.len() as u64);
+		if self.buf_len > 0 {
+			let n = ::core::cmp::min(64 - self.buf_len, data.len());
+			self.buf[self.buf_len ..][.. n].copy_from_slice(&data[.. n]);
+			self.buf_len += n;
+			data = &data[n..];
+			if self.buf_len < 64 {
+				return ;
+			}
+			let block = self.buf;
+			self.compress(&block);
+			self.buf_len = 0;
+		}
+		while data.len() >= 64
+		{
+			let (block, rest) = data.split_at(64);
+			self.compress(block);
+			data = rest;
+		}
+		self.buf[.. data.len()].copy_from_slice(data);
+		self.buf_len = data.len();
+	}
+
+	/// Complete the hash, returning the digest
+	pub fn finish(mut self) -> [u8; 32] {
+		let bit_len = self.total_len.wrapping_mul(8);
+		// Padding: a single 1 bit, zeroes up to 56 mod 64, then the big-endian bit length
+		let pad_len = if self.buf_len < 56 { 56 - self.buf_len } else { 120 - self.buf_len };
+		let mut pad = [0u8; 72];
+		pad[0] = 0x80;
+		for i in 0 .. 8
+		{
+			pad[pad_len + i] = (bit_len >> (56 - i * 8)) as u8;
+		}
+		// NOTE: `update` also adds to `total_len`, but it's already been captured above
+		self.update(&pad[.. pad_len + 8]);
+		debug_assert!(self.buf_len == 0);
+
+		let mut rv = [0; 32];
+		for (d, s) in rv.chunks_mut(4).zip(self.state.iter())
+		{
+			d[0] = (s >> 24) as u8;
+			d[1] = (s >> 16) as u8;
+			d[2] = (s >> 8) as u8;
+			d[3] = *s as u8;
+		}
+		rv
+	}
+
+	fn compress(&mut self, block: &[u8]) {
+		let mut w = [0u32; 64];
+		for (i, b) in block.chunks(4).enumerate()
+		{
+			w[i] = (b[0] as u32) << 24 | (b[1] as u32) << 16 | (b[2] as u32) << 8 | b[3] as u32;
+		}
+		for i in 16 .. 64
+		{
+			let s0 = w[i-15].rotate_right(7) ^ w[i-15].rotate_right(18) ^ (w[i-15] >> 3);
+			let s1 = w[i-2].rotate_right(17) ^ w[i-2].rotate_right(19) ^ (w[i-2] >> 10);
+			w[i] = w[i-16].wrapping_add(s0).wrapping_add(w[i-7]).wrapping_add(s1);
+		}
+
+		let mut v = self.state;
+		for i in 0 .. 64
+		{
+			let (a, b, c, d, e, f, g, h) = (v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7]);
+			let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+			let ch = (e & f) ^ (!e & g);
+			let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+			let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+			let maj = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(maj);
+			v = [t1.wrapping_add(t2), a, b, c, d.wrapping_add(t1), e, f, g];
+		}
+		for (s, v) in self.state.iter_mut().zip(v.iter())
+		{
+			*s = s.wrapping_add(*v);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::{crc32, sha256, Sha256};
+
+	#[test]
+	fn crc32_check_value() {
+		assert_eq!(crc32(b"123456789"), 0xCBF43926);
+		assert_eq!(crc32(b""), 0);
+	}
+
+	fn hex(s: &str) -> [u8; 32] {
+		let mut rv = [0; 32];
+		for (i, d) in rv.iter_mut().enumerate()
+		{
+			*d = u8::from_str_radix(&s[i * 2 .. i * 2 + 2], 16).unwrap();
+		}
+		rv
+	}
+
+	// NIST FIPS 180-2 example vectors
+	#[test]
+	fn sha256_vectors() {
+		assert_eq!(sha256(b""), hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+		assert_eq!(sha256(b"abc"), hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+		assert_eq!(sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+			hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"));
+	}
+	#[test]
+	fn sha256_million_a_chunked() {
+		let mut h = Sha256::new();
+		let chunk = [b'a'; 1000];
+		for _ in 0 .. 1000
+		{
+			h.update(&chunk);
+		}
+		assert_eq!(h.finish(), hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"));
+	}
+}
@@ -0,0 +1,36 @@
+//! Debugging helpers
+use core::fmt;
+
+/// Write a hex dump of `data`, 16 bytes per line with an ASCII gutter
+///
+/// Offsets are printed starting from `base` (e.g. the buffer's address, or its offset in a file). Works
+/// with any writer, e.g. `EfiLogger` or `serial::SerialPort`.
+///
+/// ```text
+/// 00001000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 2e 0a 00 00  |Hello, world....|
+/// ```
+pub fn hexdump<W: fmt::Write>(out: &mut W, data: &[u8], base: u64) -> fmt::Result {
+	for (i, line) in data.chunks(16).enumerate()
+	{
+		write!(out, "{:08x} ", base + (i * 16) as u64)?;
+		for j in 0 .. 16
+		{
+			if j == 8 {
+				out.write_str(" ")?;
+			}
+			match line.get(j)
+			{
+			Some(b) => write!(out, " {:02x}", b)?,
+			None => out.write_str("   ")?,
+			}
+		}
+		out.write_str("  |")?;
+		for &b in line
+		{
+			let c = if b >= 0x20 && b < 0x7F { b as char } else { '.' };
+			out.write_char(c)?;
+		}
+		out.write_str("|\n")?;
+	}
+	Ok( () )
+}
@@ -0,0 +1,290 @@
+//! Direct (linear) framebuffer access
+//!
+//! Unlike `GraphicsOutput`, these only need the framebuffer's address and layout, so keep working after
+//! `exit_boot_services`.
+use super::Status;
+use boot_services::protocols::{GraphicsOutput, PixelFormat, PixelBitmask, BltPixel, FramebufferInfo, encode_pixel};
+use font;
+
+/// Linear 32-bit-per-pixel framebuffer
+pub struct Framebuffer
+{
+	base: *mut u32,
+	width: usize,
+	height: usize,
+	/// Pixels per scanline (may be larger than `width`)
+	stride: usize,
+	format: PixelFormat,
+	masks: PixelBitmask,
+}
+impl Framebuffer
+{
+	/// Wrap a framebuffer at the specified address
+	///
+	/// Returns `UNSUPPORTED` if the format is `BltOnly` (there's no linear framebuffer)
+	///
+	/// UNSAFE: The memory must be a valid framebuffer of at least `stride * height` 32-bit pixels, and must
+	/// not be accessed by anything else while this exists
+	pub unsafe fn new(base: *mut u8, width: usize, height: usize, stride: usize, format: PixelFormat, masks: PixelBitmask) -> Result<Framebuffer, Status> {
+		if format == PixelFormat::BltOnly {
+			return Err(::status::UNSUPPORTED);
+		}
+		assert!(width <= stride);
+		Ok(Framebuffer {
+			base: base as *mut u32,
+			width: width,
+			height: height,
+			stride: stride,
+			format: format,
+			masks: masks,
+			})
+	}
+
+	/// Wrap the framebuffer described by a `GraphicsOutput::snapshot`
+	///
+	/// UNSAFE: The framebuffer must still be in the described mode, and (after `exit_boot_services`) be
+	/// identity mapped or otherwise accessible at `info.base`
+	pub unsafe fn from_info(info: &FramebufferInfo) -> Result<Framebuffer, Status> {
+		Framebuffer::new(info.base as usize as *mut u8,
+			info.horizontal_resolution as usize, info.vertical_resolution as usize, info.pixels_per_scanline as usize,
+			info.pixel_format, info.pixel_information
+			)
+	}
+
+	/// Wrap the framebuffer for the current mode of a `GraphicsOutput` instance
+	///
+	/// The returned value remains valid after `exit_boot_services` (as long as the mode isn't changed)
+	pub fn from_graphics_output(go: &GraphicsOutput) -> Result<Framebuffer, Status> {
+		// SAFE: (assumed) The firmware's reported layout is correct, and the mode is current
+		unsafe { Framebuffer::from_info(&go.snapshot()) }
+	}
+
+	#[inline]
+	pub fn width(&self) -> usize {
+		self.width
+	}
+	#[inline]
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	/// Convert a pixel to the framebuffer's native representation
+	pub fn encode(&self, px: BltPixel) -> u32 {
+		encode_pixel(px, self.format, &self.masks)
+	}
+
+	/// Convert a pixel from the framebuffer's native representation (the inverse of `encode`)
+	pub fn decode(&self, v: u32) -> BltPixel {
+		fn unscale(v: u32, mask: u32) -> u8 {
+			if mask == 0 {
+				return 0;
+			}
+			let shift = mask.trailing_zeros();
+			let bits = (mask >> shift).count_ones();
+			let v = (v & mask) >> shift;
+			(if bits >= 8 { v >> (bits - 8) } else { v << (8 - bits) }) as u8
+		}
+		match self.format
+		{
+		PixelFormat::RGBX => BltPixel::rgb(v as u8, (v >> 8) as u8, (v >> 16) as u8),
+		PixelFormat::BGRX => BltPixel::rgb((v >> 16) as u8, (v >> 8) as u8, v as u8),
+		PixelFormat::BitMask => BltPixel::rgb(unscale(v, self.masks.red_mask), unscale(v, self.masks.green_mask), unscale(v, self.masks.blue_mask)),
+		PixelFormat::BltOnly => BltPixel::BLACK,
+		}
+	}
+
+	/// Set a single pixel (out-of-range coordinates are ignored)
+	pub fn put_pixel(&mut self, x: usize, y: usize, px: BltPixel) {
+		if x < self.width && y < self.height {
+			let v = self.encode(px);
+			// SAFE: In range (checked above)
+			unsafe { ::core::ptr::write_volatile(self.base.offset((y * self.stride + x) as isize), v); }
+		}
+	}
+
+	/// Fill a rectangle (clipped to the framebuffer)
+	pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, px: BltPixel) {
+		let v = self.encode(px);
+		let x_end = ::core::cmp::min(x.saturating_add(w), self.width);
+		let y_end = ::core::cmp::min(y.saturating_add(h), self.height);
+		for row in y .. y_end {
+			for col in x .. x_end {
+				// SAFE: In range (clipped above)
+				unsafe { ::core::ptr::write_volatile(self.base.offset((row * self.stride + col) as isize), v); }
+			}
+		}
+	}
+
+	/// Draw a one-pixel line from `(x0,y0)` to `(x1,y1)` inclusive (clipped to the framebuffer)
+	pub fn draw_line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, px: BltPixel) {
+		// Bresenham's algorithm, stepping along the major axis with an integer error term
+		let (mut x, mut y) = (x0 as isize, y0 as isize);
+		let (x1, y1) = (x1 as isize, y1 as isize);
+		let dx = (x1 - x).abs();
+		let dy = -(y1 - y).abs();
+		let sx = if x < x1 { 1 } else { -1 };
+		let sy = if y < y1 { 1 } else { -1 };
+		let mut err = dx + dy;
+		loop
+		{
+			self.put_pixel(x as usize, y as usize, px);
+			if x == x1 && y == y1 {
+				break;
+			}
+			let e2 = 2 * err;
+			if e2 >= dy {
+				err += dy;
+				x += sx;
+			}
+			if e2 <= dx {
+				err += dx;
+				y += sy;
+			}
+		}
+	}
+
+	/// Draw the one-pixel border of a rectangle (clipped to the framebuffer)
+	pub fn draw_rect_outline(&mut self, x: usize, y: usize, w: usize, h: usize, px: BltPixel) {
+		if w == 0 || h == 0 {
+			return ;
+		}
+		self.fill_rect(x, y, w, 1, px);
+		self.fill_rect(x, y.saturating_add(h - 1), w, 1, px);
+		self.fill_rect(x, y, 1, h, px);
+		self.fill_rect(x.saturating_add(w - 1), y, 1, h, px);
+	}
+
+	/// Blend `px` over a rectangle (clipped to the framebuffer), e.g. for a translucent menu background
+	///
+	/// Each channel becomes `(src * alpha + dst * (255 - alpha) + 127) / 255` (rounded to nearest), so an
+	/// `alpha` of 255 is the same as `fill_rect` and 0 leaves the contents unchanged. NOTE: This reads the
+	/// framebuffer back, which is slow on some hardware - prefer blending into a scratch buffer for large areas.
+	pub fn blend_rect(&mut self, x: usize, y: usize, w: usize, h: usize, px: BltPixel, alpha: u8) {
+		fn blend(src: u8, dst: u8, alpha: u32) -> u8 {
+			((src as u32 * alpha + dst as u32 * (255 - alpha) + 127) / 255) as u8
+		}
+		let alpha = alpha as u32;
+		let x_end = ::core::cmp::min(x.saturating_add(w), self.width);
+		let y_end = ::core::cmp::min(y.saturating_add(h), self.height);
+		for row in y .. y_end {
+			for col in x .. x_end {
+				// SAFE: In range (clipped above)
+				unsafe {
+					let p = self.base.offset((row * self.stride + col) as isize);
+					let dst = self.decode(::core::ptr::read_volatile(p));
+					let v = self.encode(BltPixel::rgb(blend(px.red, dst.red, alpha), blend(px.green, dst.green, alpha), blend(px.blue, dst.blue, alpha)));
+					::core::ptr::write_volatile(p, v);
+				}
+			}
+		}
+	}
+
+	/// Move the contents up by `lines` pixel rows, filling the exposed area with `fill`
+	pub fn scroll_up(&mut self, lines: usize, fill: BltPixel) {
+		let lines = ::core::cmp::min(lines, self.height);
+		// SAFE: Both source and destination are within the framebuffer, `copy` handles the overlap
+		unsafe {
+			::core::ptr::copy(self.base.offset((lines * self.stride) as isize), self.base, (self.height - lines) * self.stride);
+		}
+		let (w, h) = (self.width, self.height);
+		self.fill_rect(0, h - lines, w, lines, fill);
+	}
+}
+
+/// Text console rendered directly to a framebuffer with the bundled font
+///
+/// Handles `\n` (newline), `\r` (carriage return), wraps at the end of a line, and scrolls when the cursor
+/// reaches the bottom. Characters not in the font are drawn as `?`.
+pub struct FramebufferConsole
+{
+	fb: Framebuffer,
+	col: usize,
+	row: usize,
+	fg: BltPixel,
+	bg: BltPixel,
+}
+impl FramebufferConsole
+{
+	/// Create a console (white on black) with the cursor at the top-left
+	pub fn new(fb: Framebuffer) -> FramebufferConsole {
+		FramebufferConsole {
+			fb: fb,
+			col: 0,
+			row: 0,
+			fg: BltPixel::WHITE,
+			bg: BltPixel::BLACK,
+			}
+	}
+
+	/// Number of text columns
+	pub fn columns(&self) -> usize {
+		self.fb.width() / font::WIDTH
+	}
+	/// Number of text rows
+	pub fn rows(&self) -> usize {
+		self.fb.height() / font::HEIGHT
+	}
+	/// Underlying framebuffer
+	pub fn framebuffer(&mut self) -> &mut Framebuffer {
+		&mut self.fb
+	}
+
+	/// Set the colours used for subsequent output
+	pub fn set_colors(&mut self, fg: BltPixel, bg: BltPixel) {
+		self.fg = fg;
+		self.bg = bg;
+	}
+	/// Clear the screen to the background colour, and return the cursor to the top-left
+	pub fn clear(&mut self) {
+		let (w, h, bg) = (self.fb.width(), self.fb.height(), self.bg);
+		self.fb.fill_rect(0, 0, w, h, bg);
+		self.col = 0;
+		self.row = 0;
+	}
+
+	/// Output a single character
+	pub fn write_char(&mut self, c: char) {
+		if self.columns() == 0 || self.rows() == 0 {
+			return;
+		}
+		match c
+		{
+		'\n' => self.newline(),
+		'\r' => self.col = 0,
+		_ => {
+			if self.col == self.columns() {
+				self.newline();
+			}
+			let glyph = font::glyph(c).or(font::glyph('?')).unwrap();
+			let (x, y) = (self.col * font::WIDTH, self.row * font::HEIGHT);
+			for (dy, bits) in glyph.iter().enumerate() {
+				for dx in 0 .. font::WIDTH {
+					let px = if bits >> dx & 1 != 0 { self.fg } else { self.bg };
+					self.fb.put_pixel(x + dx, y + dy, px);
+				}
+			}
+			self.col += 1;
+			},
+		}
+	}
+
+	fn newline(&mut self) {
+		self.col = 0;
+		if self.row + 1 == self.rows() {
+			let bg = self.bg;
+			self.fb.scroll_up(font::HEIGHT, bg);
+		}
+		else {
+			self.row += 1;
+		}
+	}
+}
+impl ::core::fmt::Write for FramebufferConsole
+{
+	fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+		for c in s.chars() {
+			self.write_char(c);
+		}
+		Ok( () )
+	}
+}
@@ -0,0 +1,28 @@
+//! GUID Partition Table (GPT) definitions
+use Guid;
+
+/// Unused partition entry
+pub const UNUSED_ENTRY_GUID: Guid = Guid(0x00000000, 0x0000, 0x0000, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+/// EFI System Partition (ESP)
+pub const EFI_SYSTEM_PARTITION_GUID: Guid = Guid(0xc12a7328, 0xf81f, 0x11d2, [0xba, 0x4b, 0x00, 0xa0, 0xc9, 0x3e, 0xc9, 0x3b]);
+/// BIOS boot partition (used by GRUB on BIOS/GPT systems)
+pub const BIOS_BOOT_PARTITION_GUID: Guid = Guid(0x21686148, 0x6449, 0x6e6f, [0x74, 0x4e, 0x65, 0x65, 0x64, 0x45, 0x46, 0x49]);
+/// Microsoft basic data (FAT, NTFS, exFAT)
+pub const MICROSOFT_BASIC_DATA_GUID: Guid = Guid(0xebd0a0a2, 0xb9e5, 0x4433, [0x87, 0xc0, 0x68, 0xb6, 0xb7, 0x26, 0x99, 0xc7]);
+/// Linux filesystem data
+pub const LINUX_FILESYSTEM_GUID: Guid = Guid(0x0fc63daf, 0x8483, 0x4772, [0x8e, 0x79, 0x3d, 0x69, 0xd8, 0x47, 0x7d, 0xe4]);
+/// Linux swap
+pub const LINUX_SWAP_GUID: Guid = Guid(0x0657fd6d, 0xa4ab, 0x43c4, [0x84, 0xe5, 0x09, 0x33, 0xc8, 0x4b, 0x4f, 0x4f]);
+
+/// Human-readable name for a well-known partition type GUID
+pub fn partition_type_name(ty: &Guid) -> Option<&'static str> {
+	const NAMES: [(Guid, &'static str); 6] = [
+		(UNUSED_ENTRY_GUID, "Unused"),
+		(EFI_SYSTEM_PARTITION_GUID, "EFI System Partition"),
+		(BIOS_BOOT_PARTITION_GUID, "BIOS boot"),
+		(MICROSOFT_BASIC_DATA_GUID, "Microsoft basic data"),
+		(LINUX_FILESYSTEM_GUID, "Linux filesystem"),
+		(LINUX_SWAP_GUID, "Linux swap"),
+		];
+	NAMES.iter().find(|e| e.0 == *ty).map(|e| e.1)
+}
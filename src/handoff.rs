@@ -0,0 +1,233 @@
+//! Kernel handoff information
+//!
+//! `BootInfo` packages up what a kernel usually needs from the firmware (usable RAM, the framebuffer, the
+//! ACPI root pointer, and the command line) in a `#[repr(C)]` form that can be passed across the
+//! bootloader/kernel boundary.
+//!
+//! Ordering matters: everything must be gathered *before* `exit_boot_services` (the protocols and the
+//! memory map are only available while the firmware is in control), and the memory map has to be the last
+//! thing fetched, as any allocation after it invalidates the map key. `BootInfoBuilder::exit_boot_services`
+//! does the steps in the right order.
+//!
+//! ```no_run
+//! # fn f(st: &::uefi::SystemTable, image_handle: ::uefi::Handle) -> Result<(), ::uefi::Status> {
+//! let info = unsafe {
+//! 	::uefi::handoff::BootInfoBuilder::new()
+//! 		.command_line("root=/dev/sda1")
+//! 		.exit_boot_services(st, image_handle)?
+//! 	};
+//! // No more boot services (or console output) from here on
+//! # let _ = info; Ok(())
+//! # }
+//! ```
+use core::{mem, str};
+use super::{Status, Handle, PhysicalAddress, SystemTable};
+use boot_services::{BootServices, MemoryType};
+use boot_services::protocols::{GraphicsOutput, PixelFormat, FramebufferInfo};
+
+/// Maximum number of entries in `BootInfo::memory_regions`
+pub const MAX_MEMORY_REGIONS: usize = 128;
+/// Maximum length (in bytes) of `BootInfo::command_line`
+pub const MAX_COMMAND_LINE: usize = 256;
+
+/// Number of spare descriptors allowed for when allocating the memory map buffer
+const MAP_SLACK_DESCRIPTORS: usize = 8;
+
+/// Range of usable physical memory
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct MemoryRegion
+{
+	pub base: PhysicalAddress,
+	/// Length in 4KiB pages
+	pub pages: u64,
+}
+impl MemoryRegion
+{
+	/// Physical address just past the end of the region
+	pub fn end(&self) -> PhysicalAddress {
+		self.base + self.pages * 0x1000
+	}
+}
+
+/// Information for the kernel, gathered from the firmware before `exit_boot_services`
+#[repr(C)]
+pub struct BootInfo
+{
+	/// Usable RAM, in firmware order (with adjacent regions merged). Only the first `memory_region_count`
+	/// entries are valid.
+	///
+	/// This includes memory used by boot services (which is free once they've exited), but not
+	/// `LoaderCode`/`LoaderData`, which hold the loaded image and its own allocations.
+	pub memory_regions: [MemoryRegion; MAX_MEMORY_REGIONS],
+	pub memory_region_count: usize,
+	/// Linear framebuffer (only valid if `has_framebuffer` is set)
+	pub framebuffer: FramebufferInfo,
+	pub has_framebuffer: bool,
+	/// Physical address of the ACPI RSDP (zero if the firmware didn't provide one)
+	pub rsdp_address: PhysicalAddress,
+	/// UTF-8 command line, the first `command_line_len` bytes are valid
+	pub command_line: [u8; MAX_COMMAND_LINE],
+	pub command_line_len: usize,
+}
+impl BootInfo
+{
+	pub fn memory_regions(&self) -> &[MemoryRegion] {
+		&self.memory_regions[..self.memory_region_count]
+	}
+	pub fn framebuffer(&self) -> Option<&FramebufferInfo> {
+		if self.has_framebuffer {
+			Some(&self.framebuffer)
+		}
+		else {
+			None
+		}
+	}
+	pub fn rsdp_address(&self) -> Option<PhysicalAddress> {
+		if self.rsdp_address != 0 {
+			Some(self.rsdp_address)
+		}
+		else {
+			None
+		}
+	}
+	/// Command line (empty if the stored bytes aren't valid UTF-8)
+	pub fn command_line(&self) -> &str {
+		str::from_utf8(&self.command_line[..self.command_line_len]).unwrap_or("")
+	}
+
+	fn push_region(&mut self, base: PhysicalAddress, pages: u64) -> Result<(), Status> {
+		if self.memory_region_count > 0 {
+			let last = &mut self.memory_regions[self.memory_region_count - 1];
+			if last.end() == base {
+				last.pages += pages;
+				return Ok( () );
+			}
+		}
+		if self.memory_region_count == MAX_MEMORY_REGIONS {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		self.memory_regions[self.memory_region_count] = MemoryRegion { base: base, pages: pages };
+		self.memory_region_count += 1;
+		Ok( () )
+	}
+}
+
+/// Builder that gathers a `BootInfo` and exits boot services
+pub struct BootInfoBuilder<'a>
+{
+	command_line: &'a str,
+}
+impl<'a> BootInfoBuilder<'a>
+{
+	pub fn new() -> BootInfoBuilder<'a> {
+		BootInfoBuilder {
+			command_line: "",
+			}
+	}
+	/// Set the command line passed to the kernel (at most `MAX_COMMAND_LINE` bytes)
+	pub fn command_line(self, command_line: &'a str) -> BootInfoBuilder<'a> {
+		BootInfoBuilder {
+			command_line: command_line,
+			}
+	}
+
+	/// Gather the boot information, then exit boot services
+	///
+	/// The framebuffer and ACPI pointer are collected first, then the memory map is fetched (into a pool
+	/// buffer that's only freed on failure) and boot services exited straight away. If anything fails before the exit
+	/// (e.g. `BUFFER_TOO_SMALL` for an oversized command line or too many memory regions), boot services
+	/// are still available. The memory map is re-fetched once if the firmware reports that it changed.
+	///
+	/// UNSAFE: On success, boot services and everything obtained through them (protocols, the console, pool
+	/// allocations) are gone and must not be used again. See `BootServices::exit_boot_services`.
+	pub unsafe fn exit_boot_services(self, st: &SystemTable, image_handle: Handle) -> Result<BootInfo, Status> {
+		let bs = st.boot_services();
+		if self.command_line.len() > MAX_COMMAND_LINE {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+
+		// SAFE: All fields are integers, bools, or a C-style enum with a zero variant
+		let mut info: BootInfo = mem::zeroed();
+		info.command_line[..self.command_line.len()].copy_from_slice(self.command_line.as_bytes());
+		info.command_line_len = self.command_line.len();
+		if let Some(fb) = get_framebuffer(bs) {
+			info.framebuffer = fb;
+			info.has_framebuffer = true;
+		}
+		info.rsdp_address = find_rsdp(st);
+
+		// Allocate the map buffer - after this, nothing may allocate (or free) until the exit
+		let (size, descriptor_size) = bs.memory_map_size()?;
+		let buf_size = size + MAP_SLACK_DESCRIPTORS * descriptor_size;
+		let buf = ::core::slice::from_raw_parts_mut(bs.allocate_pool::<u8>(buf_size)?, buf_size);
+
+		let mut retried = false;
+		loop
+		{
+			let key = match fill_regions(&mut info, bs, buf)
+				{
+				Ok(k) => k,
+				Err(e) => {
+					bs.free_pool(buf.as_ptr());
+					return Err(e);
+					},
+				};
+			match bs.exit_boot_services(image_handle, key)
+			{
+			Ok( () ) => return Ok(info),
+			// The map changed (e.g. a timer event fired), the spec allows retrying with a fresh map
+			Err(::status::INVALID_PARAMETER) if !retried => { retried = true; },
+			Err(e) => {
+				bs.free_pool(buf.as_ptr());
+				return Err(e);
+				},
+			}
+		}
+	}
+}
+
+fn get_framebuffer(bs: &BootServices) -> Option<FramebufferInfo> {
+	match GraphicsOutput::new(bs)
+	{
+	Ok(gop) => {
+		let fb = gop.snapshot();
+		if fb.pixel_format == PixelFormat::BltOnly {
+			None
+		}
+		else {
+			Some(fb)
+		}
+		},
+	Err(_) => None,
+	}
+}
+
+/// Locate the ACPI RSDP, preferring the 2.0+ table
+fn find_rsdp(st: &SystemTable) -> PhysicalAddress {
+	let tables = st.configuraton_table();
+	let find = |guid| tables.iter().find(|t| t.vendor_guid == guid).map(|t| t.vendor_table);
+	// NOTE: Boot-time memory is identity mapped, so the pointer is the physical address
+	match find(::ACPI_20_TABLE_GUID).or_else(|| find(::ACPI_TABLE_GUID))
+	{
+	Some(p) => p as usize as PhysicalAddress,
+	None => 0,
+	}
+}
+
+/// Fetch the memory map and record the usable regions, returning the map key
+fn fill_regions(info: &mut BootInfo, bs: &BootServices, buf: &mut [u8]) -> Result<usize, Status> {
+	let map = bs.get_memory_map(buf)?;
+	info.memory_regions = [MemoryRegion { base: 0, pages: 0 }; MAX_MEMORY_REGIONS];
+	info.memory_region_count = 0;
+	for desc in map.iter()
+	{
+		let usable = desc.ty == MemoryType::ConventionalMemory as u32
+			|| desc.ty == MemoryType::BootServicesCode as u32
+			|| desc.ty == MemoryType::BootServicesData as u32;
+		if usable {
+			info.push_region(desc.physical_start, desc.number_of_pages)?;
+		}
+	}
+	Ok(map.key())
+}
@@ -0,0 +1,260 @@
+//! Windows bitmap (BMP) encoding and decoding
+//!
+//! Files are written as uncompressed 24-bit bitmaps. Each row is stored bottom-up as B,G,R triples, and
+//! padded with zero bytes to a multiple of 4 bytes (so a row is `(width * 3 + 3) & !3` bytes long).
+//!
+//! Decoding supports uncompressed 24 and 32-bit bitmaps (bottom-up or top-down).
+use core::ptr;
+use boot_services::{BootServices, MemoryType, PoolVec};
+use boot_services::protocols::{File, BltPixel};
+use Status;
+
+/// Size of the file header plus the `BITMAPINFOHEADER`
+const HEADER_SIZE: usize = 14 + 40;
+/// Resolution stored in the header (72 DPI, in pixels per metre)
+const PIXELS_PER_METRE: u32 = 2835;
+
+/// Length in bytes of a 24-bit row, including padding
+fn row_size(width: u32) -> usize {
+	(width as usize * 3 + 3) & !3
+}
+
+/// Write `pixels` (row-major, top row first, `width * height` entries) to `file` as a 24-bit BMP
+///
+/// Returns `INVALID_PARAMETER` if `pixels` is too short, or the image is too large for a BMP
+pub fn write_bmp(file: &mut File, pixels: &[BltPixel], width: u32, height: u32) -> Result<(), Status> {
+	let count = width as usize * height as usize;
+	if pixels.len() < count || width > i32::max_value() as u32 || height > i32::max_value() as u32 {
+		return Err(::status::INVALID_PARAMETER);
+	}
+	let image_size = row_size(width) as u64 * height as u64;
+	if HEADER_SIZE as u64 + image_size > u32::max_value() as u64 {
+		return Err(::status::INVALID_PARAMETER);
+	}
+	let image_size = image_size as u32;
+
+	let mut hdr = [0u8; HEADER_SIZE];
+	// BITMAPFILEHEADER
+	hdr[0] = b'B';
+	hdr[1] = b'M';
+	put_u32(&mut hdr[2..], HEADER_SIZE as u32 + image_size);
+	put_u32(&mut hdr[10..], HEADER_SIZE as u32);
+	// BITMAPINFOHEADER
+	put_u32(&mut hdr[14..], 40);
+	put_u32(&mut hdr[18..], width);
+	put_u32(&mut hdr[22..], height);	// Positive height = bottom-up
+	put_u16(&mut hdr[26..], 1);	// Planes
+	put_u16(&mut hdr[28..], 24);	// Bits per pixel
+	put_u32(&mut hdr[30..], 0);	// BI_RGB (uncompressed)
+	put_u32(&mut hdr[34..], image_size);
+	put_u32(&mut hdr[38..], PIXELS_PER_METRE);
+	put_u32(&mut hdr[42..], PIXELS_PER_METRE);
+	write_all(file, &hdr)?;
+
+	let padding = row_size(width) - width as usize * 3;
+	let mut buf = [0u8; 3 * 64];
+	for row in (0 .. height as usize).rev()
+	{
+		let row = &pixels[row * width as usize ..][.. width as usize];
+		for chunk in row.chunks(64)
+		{
+			for (d, px) in buf.chunks_mut(3).zip(chunk)
+			{
+				d[0] = px.blue;
+				d[1] = px.green;
+				d[2] = px.red;
+			}
+			write_all(file, &buf[.. chunk.len() * 3])?;
+		}
+		write_all(file, &[0; 3][..padding])?;
+	}
+	Ok( () )
+}
+
+/// Decoded bitmap, with pixels converted to `BltPixel` (top row first) in a pool allocation
+pub struct BmpImage<'a>
+{
+	width: u32,
+	height: u32,
+	pixels: PoolVec<'a, BltPixel>,
+}
+impl<'a> BmpImage<'a>
+{
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+	/// Row-major pixels, ready to pass to `GraphicsOutput::blt_to_video`
+	pub fn pixels(&self) -> &[BltPixel] {
+		&self.pixels
+	}
+}
+
+/// Decode an in-memory BMP file
+///
+/// Returns `INVALID_PARAMETER` if the data isn't a well-formed BMP, and `UNSUPPORTED` for compressed,
+/// palettized, or other non-24/32-bit formats.
+pub fn decode<'a>(bs: &'a BootServices, data: &[u8]) -> Result<BmpImage<'a>, Status> {
+	let hdr = Header::parse(data)?;
+	let count = hdr.width.checked_mul(hdr.height).ok_or(::status::INVALID_PARAMETER)?;
+
+	let mut pixels = bs.allocate_pool_vec::<BltPixel>(MemoryType::LoaderData, count)?;
+	let dst = pixels.as_mut_ptr();
+	for y in 0 .. hdr.height
+	{
+		for x in 0 .. hdr.width
+		{
+			// SAFE: Index is less than `count`, which is the allocation's capacity
+			unsafe {
+				ptr::write(dst.offset((y * hdr.width + x) as isize), hdr.pixel(data, x, y));
+			}
+		}
+	}
+	// SAFE: All `count` entries were initialised above
+	unsafe { pixels.set_len(count); }
+	Ok(BmpImage {
+		width: hdr.width as u32,
+		height: hdr.height as u32,
+		pixels: pixels,
+		})
+}
+
+/// Validated header of a supported bitmap
+struct Header
+{
+	width: usize,
+	height: usize,
+	top_down: bool,
+	bytes_pp: usize,
+	row_len: usize,
+	pixel_offset: usize,
+}
+impl Header
+{
+	/// Parse and validate the headers, checking that the pixel data is within `data`
+	fn parse(data: &[u8]) -> Result<Header, Status> {
+		if data.len() < HEADER_SIZE || &data[0..2] != b"BM" {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let pixel_offset = get_u32(&data[10..]) as usize;
+		let info_size = get_u32(&data[14..]) as usize;
+		if info_size < 40 || 14 + info_size > data.len() {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		// The pixels can't overlap the headers
+		if pixel_offset < 14 + info_size {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let width = get_u32(&data[18..]) as i32;
+		let height = get_u32(&data[22..]) as i32;
+		let planes = get_u16(&data[26..]);
+		let bpp = get_u16(&data[28..]);
+		let compression = get_u32(&data[30..]);
+		if width <= 0 || height == 0 || height == i32::min_value() || planes != 1 {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		if compression != 0 || (bpp != 24 && bpp != 32) {
+			return Err(::status::UNSUPPORTED);
+		}
+		// Negative height means the rows are stored top-down
+		let (width, height, top_down) = (width as usize, height.abs() as usize, height < 0);
+		let bytes_pp = bpp as usize / 8;
+		let row_len = (width * bytes_pp + 3) & !3;
+		let image_end = row_len.checked_mul(height).and_then(|v| v.checked_add(pixel_offset));
+		match image_end
+		{
+		Some(end) if end <= data.len() => {},
+		_ => return Err(::status::INVALID_PARAMETER),
+		}
+		Ok(Header {
+			width: width,
+			height: height,
+			top_down: top_down,
+			bytes_pp: bytes_pp,
+			row_len: row_len,
+			pixel_offset: pixel_offset,
+			})
+	}
+
+	/// Pixel at (`x`, `y`), counting rows from the top of the image
+	fn pixel(&self, data: &[u8], x: usize, y: usize) -> BltPixel {
+		let src_row = if self.top_down { y } else { self.height - 1 - y };
+		let px = &data[self.pixel_offset + src_row * self.row_len + x * self.bytes_pp ..];
+		BltPixel::rgb(px[2], px[1], px[0])
+	}
+}
+
+fn write_all(file: &mut File, mut data: &[u8]) -> Result<(), Status> {
+	while !data.is_empty()
+	{
+		match file.write(data)?
+		{
+		0 => return Err(::status::DEVICE_ERROR),
+		n => data = &data[n..],
+		}
+	}
+	Ok( () )
+}
+
+fn put_u16(dst: &mut [u8], v: u16) {
+	dst[0] = v as u8;
+	dst[1] = (v >> 8) as u8;
+}
+fn put_u32(dst: &mut [u8], v: u32) {
+	put_u16(&mut dst[0..], v as u16);
+	put_u16(&mut dst[2..], (v >> 16) as u16);
+}
+
+fn get_u16(src: &[u8]) -> u16 {
+	src[0] as u16 | (src[1] as u16) << 8
+}
+fn get_u32(src: &[u8]) -> u32 {
+	get_u16(&src[0..]) as u32 | (get_u16(&src[2..]) as u32) << 16
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::{Header, HEADER_SIZE, put_u16, put_u32};
+	use boot_services::protocols::BltPixel;
+
+	/// 2x2 24-bit bottom-up bitmap: red, green on top, blue, white below
+	fn tiny_bmp() -> [u8; HEADER_SIZE + 16] {
+		let mut data = [0u8; HEADER_SIZE + 16];
+		data[0] = b'B';
+		data[1] = b'M';
+		put_u32(&mut data[2..], (HEADER_SIZE + 16) as u32);
+		put_u32(&mut data[10..], HEADER_SIZE as u32);
+		put_u32(&mut data[14..], 40);
+		put_u32(&mut data[18..], 2);
+		put_u32(&mut data[22..], 2);
+		put_u16(&mut data[26..], 1);
+		put_u16(&mut data[28..], 24);
+		// Bottom row first, B,G,R triples padded to 8 bytes
+		data[HEADER_SIZE ..].copy_from_slice(&[
+			0xFF,0x00,0x00, 0xFF,0xFF,0xFF, 0,0,
+			0x00,0x00,0xFF, 0x00,0xFF,0x00, 0,0,
+			]);
+		data
+	}
+
+	#[test]
+	fn decode_tiny() {
+		let data = tiny_bmp();
+		let hdr = Header::parse(&data).ok().unwrap();
+		assert_eq!((hdr.width, hdr.height, hdr.top_down), (2, 2, false));
+		assert!(hdr.pixel(&data, 0, 0) == BltPixel::RED);
+		assert!(hdr.pixel(&data, 1, 0) == BltPixel::GREEN);
+		assert!(hdr.pixel(&data, 0, 1) == BltPixel::BLUE);
+		assert!(hdr.pixel(&data, 1, 1) == BltPixel::WHITE);
+	}
+
+	#[test]
+	fn reject_pixels_inside_header() {
+		let mut data = tiny_bmp();
+		put_u32(&mut data[10..], 14);
+		assert!(Header::parse(&data).err() == Some(::status::INVALID_PARAMETER));
+	}
+}
@@ -0,0 +1,3 @@
+//! Simple image formats (for screenshots and splash screens)
+
+pub mod bmp;
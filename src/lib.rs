@@ -17,11 +17,19 @@
 #![feature(try_trait)]	// Makes Status a little easier to use
 #![feature(ptr_internals)]	// rawptr as_ref
 
+#[cfg(feature="log")]
+extern crate log;
+
 pub use self::str16::Str16;
-pub use self::str16::{CStr16Ptr, CStr16};
+pub use self::str16::{CStr16Ptr, CStr16PtrExt, CStr16, ArrayCStr16};
+pub use self::str16::utf16_len;
 
 pub use self::con::{EfiLogger};
+pub use self::con::{LogLevel, log_at};
 pub use self::con::{InputKey, SimpleInputInterface, SimpleTextOutputInterface};
+pub use self::con::{Color, AttributeGuard};
+pub use self::con::{scan_codes, keys};
+pub use self::con::read_line;
 
 pub use self::status::Status;
 
@@ -42,12 +50,60 @@ mod str16;
 pub mod status;
 pub mod runtime_services;
 pub mod boot_services;
+pub mod args;
+pub mod panic;
+pub mod font;
+pub mod framebuffer;
+pub mod handoff;
+pub mod debug;
+pub mod image;
+pub mod gpt;
+pub mod crypto;
+pub mod linux;
+pub mod progress;
+pub mod menu;
+#[cfg(target_arch="x86_64")]
+pub mod serial;
+#[cfg(feature="log")]
+pub mod logger;
 
 // libstd miniature clones
 pub mod borrow;
 
 pub enum Void {}
-pub type Handle = *mut Void;
+
+/// Firmware handle (an opaque pointer, e.g. an image, device, or controller handle)
+///
+/// This is only a copy of the raw handle, it doesn't own anything (and isn't released on drop).
+#[repr(transparent)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle(*mut Void);
+impl Handle
+{
+	/// Null handle (e.g. for optional handle parameters, or as an out-parameter initialiser)
+	#[inline]
+	pub fn null() -> Handle {
+		Handle(::core::ptr::null_mut())
+	}
+	#[inline]
+	pub fn from_raw(p: *mut Void) -> Handle {
+		Handle(p)
+	}
+	#[inline]
+	pub fn as_raw(&self) -> *mut Void {
+		self.0
+	}
+	#[inline]
+	pub fn is_null(&self) -> bool {
+		self.0.is_null()
+	}
+}
+impl ::core::fmt::Debug for Handle
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		write!(f, "Handle({:p})", self.0)
+	}
+}
 pub type PhysicalAddress = u64;
 pub type VirtualAddress = u64;
 
@@ -55,6 +111,8 @@ pub type VirtualAddress = u64;
 pub const GRAPHICS_OUTPUT_PROTOCOL_GUID: Guid = Guid(0x9042a9de, 0x23dc, 0x4a38, [0x96,0xfb,0x7a,0xde,0xd0,0x80,0x51,0x6a]);
 pub const FILE_SYSTEM_GUID: Guid = Guid(0x964e5b22, 0x6459, 0x11d2, [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b]);
 pub const FILE_INFO_ID: Guid = Guid(0x09576e92, 0x6d3f, 0x11d2, [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b]);
+pub const FILE_SYSTEM_INFO_ID: Guid = Guid(0x09576e93, 0x6d3f, 0x11d2, [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b]);
+pub const FILE_SYSTEM_VOLUME_LABEL_ID: Guid = Guid(0xdb47d7d3, 0xfe81, 0x11d3, [0x9a, 0x35, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d]);
 pub const ACPI_TABLE_GUID: Guid = Guid(0xeb9d2d30, 0x2d88, 0x11d3, [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d]);
 pub const ACPI_20_TABLE_GUID: Guid = Guid(0x8868e871, 0xe4f1, 0x11d3, [0xbc, 0x22, 0x00, 0x80, 0xc7, 0x3c, 0x88, 0x81]);
 pub const SMBIOS_TABLE_GUID: Guid = Guid(0xeb9d2d31, 0x2d88, 0x11d3, [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d]);
@@ -62,6 +120,32 @@ pub const SMBIOS3_TABLE_GUID: Guid = Guid(0xf2fd1544, 0x9794, 0x4a2c, [0x99, 0x2
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Guid( pub u32, pub u16, pub u16, pub [u8; 8] );
+impl Guid
+{
+	/// Decode from the 16-byte on-disk/in-memory form (as used by GPT entries and device path nodes)
+	///
+	/// The first three fields are stored little-endian (bytes 0-3, 4-5, and 6-7), and the final eight bytes
+	/// are stored as-is. So `{8868E871-E4F1-11D3-BC22-0080C73C8881}` is stored as
+	/// `71 E8 68 88 F1 E4 D3 11 BC 22 00 80 C7 3C 88 81`.
+	pub fn from_bytes(b: &[u8; 16]) -> Guid {
+		Guid(
+			b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24,
+			b[4] as u16 | (b[5] as u16) << 8,
+			b[6] as u16 | (b[7] as u16) << 8,
+			[b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]],
+			)
+	}
+	/// Encode into the 16-byte form (the inverse of `from_bytes`)
+	pub fn to_bytes(&self) -> [u8; 16] {
+		let d = &self.3;
+		[
+			self.0 as u8, (self.0 >> 8) as u8, (self.0 >> 16) as u8, (self.0 >> 24) as u8,
+			self.1 as u8, (self.1 >> 8) as u8,
+			self.2 as u8, (self.2 >> 8) as u8,
+			d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7],
+			]
+	}
+}
 
 #[macro_export]
 /// Log to the provided UEFI SimpleTextOutputInterface sink
@@ -74,6 +158,35 @@ macro_rules! loge {
 	}};
 }
 
+#[macro_export]
+/// Log an informational message (`[INFO]` tag, in green) to a SimpleTextOutputInterface sink
+///
+/// Like `loge!`, the message is prefixed with the module path, see `log_at` for how colour is applied
+macro_rules! log_info {
+	($l:expr, $($t:tt)*) => {
+		$crate::log_at($l, $crate::LogLevel::Info, module_path!(), format_args!($($t)*))
+	};
+}
+#[macro_export]
+/// Log a warning (`[WARN]` tag, in yellow) to a SimpleTextOutputInterface sink
+macro_rules! log_warn {
+	($l:expr, $($t:tt)*) => {
+		$crate::log_at($l, $crate::LogLevel::Warn, module_path!(), format_args!($($t)*))
+	};
+}
+#[macro_export]
+/// Log an error (`[ERROR]` tag, in red) to a SimpleTextOutputInterface sink
+macro_rules! log_error {
+	($l:expr, $($t:tt)*) => {
+		$crate::log_at($l, $crate::LogLevel::Error, module_path!(), format_args!($($t)*))
+	};
+}
+
+/// Table revision of EFI 1.10 (the first to include the library/open-protocol services)
+pub const REVISION_1_10: u32 = (1 << 16) | 10;
+/// Table revision of UEFI 2.0
+pub const REVISION_2_00: u32 = (2 << 16) | 0;
+
 #[repr(C)]
 /// Header for a UEFI table
 pub struct TableHeader
@@ -84,18 +197,77 @@ pub struct TableHeader
 	pub crc32: u32,
 	_reserved: u32,
 }
+impl TableHeader
+{
+	/// Major component of the revision (`revision` is packed as `major << 16 | minor`)
+	#[inline]
+	pub fn revision_major(&self) -> u16 {
+		(self.revision >> 16) as u16
+	}
+	/// Minor component of the revision
+	///
+	/// NOTE: The minor is encoded as a two-digit decimal (e.g. 2.31 is `31`, 2.3 is `30`), and some
+	/// firmware uses a BCD-ish encoding instead. Compare against the `REVISION_*` constants where possible.
+	#[inline]
+	pub fn revision_minor(&self) -> u16 {
+		self.revision as u16
+	}
+}
 
 #[repr(C)]
 /// Size+Pointer array pointer
+///
+/// Access assumes (without being able to check) that, when `count` is non-zero, `data` points to `count`
+/// valid entries that live as long as the owning table and aren't modified by the firmware while borrowed.
+/// Alignment is checked in debug builds.
 pub struct SizePtr<T>
 {
 	count: usize,
 	data: *const T,
 }
+impl<T> SizePtr<T>
+{
+	/// Number of entries
+	pub fn len(&self) -> usize {
+		self.count
+	}
+	pub fn is_empty(&self) -> bool {
+		self.count == 0
+	}
+	/// Obtain a single entry, or `None` if the index is out of range
+	pub fn get(&self, i: usize) -> Option<&T> {
+		if i < self.count {
+			self.check_align();
+			// SAFE: (assumed) `data` points to `count` valid entries, index checked above
+			Some(unsafe { &*self.data.offset(i as isize) })
+		}
+		else {
+			None
+		}
+	}
+
+	/// Iterate the entries one at a time, without forming a slice over the whole (untrusted) `count`
+	pub fn iter<'s>(&'s self) -> impl Iterator<Item=&'s T> + 's {
+		self.check_align();
+		// SAFE: (assumed) `data` points to `count` valid entries, iteration is bounded by `count`
+		(0 .. self.count).map(move |i| unsafe { &*self.data.offset(i as isize) })
+	}
+
+	/// Catch misaligned firmware pointers early (in debug builds)
+	#[inline]
+	fn check_align(&self) {
+		debug_assert!(self.data as usize % ::core::mem::align_of::<T>() == 0, "SizePtr data {:p} misaligned", self.data);
+	}
+}
 impl<T> ::core::ops::Deref for SizePtr<T>
 {
 	type Target = [T];
 	fn deref(&self) -> &[T] {
+		if self.count == 0 {
+			// The pointer may be null for an empty list
+			return &[];
+		}
+		self.check_align();
 		// SAFE: (assumed) from FFI and defined to be correct
 		unsafe {
 			::core::slice::from_raw_parts(self.data, self.count)
@@ -103,6 +275,14 @@ impl<T> ::core::ops::Deref for SizePtr<T>
 	}
 }
 
+/// `TableHeader::signature` of the system table ("IBI SYST")
+pub const SYSTEM_TABLE_SIGNATURE: u64 = 0x5453_5953_2049_4249;
+/// Largest `header_size` that `SystemTable::verify` will check
+const SYSTEM_TABLE_VERIFY_MAX: usize = 512;
+
+/// Maximum length (in code units, including the NUL) scanned for `SystemTable::firmware_vendor`
+const FIRMWARE_VENDOR_MAX_LEN: usize = 256;
+
 #[repr(C)]
 /// System Table (top-level EFI structure)
 ///
@@ -131,12 +311,51 @@ pub struct SystemTable<'a>
 }
 impl<'a> SystemTable<'a>
 {
+	/// Firmware vendor string (empty if the firmware's pointer is null or unterminated)
 	#[inline]
 	pub fn firmware_vendor(&self) -> &Str16 {
-		unsafe {
-			Str16::from_nul_terminated(self.firmware_vendor)
+		// SAFE: (assumed) Pointer is from the firmware, and the scan is bounded
+		match unsafe { self.firmware_vendor.to_cstr16(FIRMWARE_VENDOR_MAX_LEN) }
+		{
+		Some(s) => s.as_str16(),
+		None => Str16::from_slice(&[]),
 		}
 	}
+	/// UEFI version reported in the system table header, as `(major, minor)`
+	#[inline]
+	pub fn uefi_version(&self) -> (u16, u16) {
+		(self.hdr.revision_major(), self.hdr.revision_minor())
+	}
+
+	/// Check the table's signature and header CRC
+	///
+	/// This is a best-effort sanity check that the firmware passed a real system table (and that it hasn't
+	/// been scribbled on), not a security measure - anything able to corrupt the table can fix up the CRC.
+	/// Uses the firmware's CRC service, or a software CRC if that's unavailable. Returns `CRC_ERROR` on a
+	/// mismatch, and `INVALID_PARAMETER` for a bad signature or header size.
+	pub fn verify(&self, bs: &boot_services::BootServices) -> Result<(), Status> {
+		let size = self.hdr.header_size as usize;
+		if self.hdr.signature != SYSTEM_TABLE_SIGNATURE || size < ::core::mem::size_of::<SystemTable>() || size > SYSTEM_TABLE_VERIFY_MAX {
+			return Err(status::INVALID_PARAMETER);
+		}
+		// Copy the table, so the CRC field can be zeroed without touching the original
+		let mut buf = [0u8; SYSTEM_TABLE_VERIFY_MAX];
+		// SAFE: (assumed) The firmware's table is at least `header_size` bytes
+		unsafe { ::core::ptr::copy_nonoverlapping(self as *const SystemTable as *const u8, buf.as_mut_ptr(), size); }
+		let crc_ofs = 16;	// Offset of `TableHeader::crc32`
+		for b in &mut buf[crc_ofs .. crc_ofs + 4] {
+			*b = 0;
+		}
+		let crc = match bs.calculate_crc32(&buf[..size])
+			{
+			Ok(v) => v,
+			Err(_) => crypto::crc32(&buf[..size]),
+			};
+		if crc != self.hdr.crc32 {
+			return Err(status::CRC_ERROR);
+		}
+		Ok( () )
+	}
 	#[inline]
 	pub fn con_in(&self) -> &SimpleInputInterface {
 		self.con_in
@@ -150,8 +369,13 @@ impl<'a> SystemTable<'a>
 		self.std_err
 	}
 
+	/// Runtime services, callable both before and after `exit_boot_services`
+	///
+	/// After the exit, prefer the `Runtime` returned by `SystemTable::exit_boot_services` (which can't
+	/// reach boot-time-only services by mistake).
 	#[inline]
 	pub fn runtime_services(&self) -> &runtime_services::RuntimeServices {
+		// SAFE: (assumed) Pointer is from the firmware, and runtime services stay valid after the exit
 		unsafe { &*self.runtime_services }
 	}
 	#[inline]
@@ -162,6 +386,44 @@ impl<'a> SystemTable<'a>
 	pub fn configuraton_table(&self) -> &[ConfigurationTable] {
 		&self.configuraton_table[..]
 	}
+
+	/// Terminate boot services, returning the services that remain usable
+	///
+	/// `map_key` must be from the most recent `get_memory_map` call, as for `BootServices::exit_boot_services`
+	/// (which this wraps). Use the returned `Runtime` from then on.
+	///
+	/// The table is borrowed mutably for as long as the `Runtime` lives, so the boot-time accessors
+	/// (`boot_services`, `con_out`, ...) can't be reached through this reference alongside it. Take the
+	/// entry point's system table as `&mut SystemTable` to use this. Other references to the table (or to
+	/// anything obtained from it earlier) aren't covered by the borrow, hence this remaining unsafe.
+	///
+	/// UNSAFE: On success, the rest of the system table (`boot_services`, the console, and every protocol)
+	/// is gone, and must not be used again through any other reference.
+	pub unsafe fn exit_boot_services<'b>(&'b mut self, image_handle: Handle, map_key: usize) -> Result<runtime_services::Runtime<'b>, Status> {
+		self.boot_services.exit_boot_services(image_handle, map_key)?;
+		Ok(runtime_services::Runtime::new(&*self.runtime_services, self.configuraton_table()))
+	}
+}
+
+/// Boot banner summary, e.g. `EDK II rev 0x10000 (UEFI 2.7), 10 configuration tables`
+impl<'a> ::core::fmt::Display for SystemTable<'a>
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		let (major, minor) = self.uefi_version();
+		write!(f, "{} rev {:#x} (", self.firmware_vendor(), self.firmware_revision)?;
+		if major < 2 {
+			// EFI 1.x uses a plain two-digit minor (e.g. `REVISION_1_10` is 1.10, not 1.1)
+			write!(f, "EFI {}.{:02}", major, minor)?;
+		}
+		else {
+			// Minor revisions are encoded as two decimal digits, the second being the "sub-minor" (e.g. 2.31 is 2.3.1)
+			write!(f, "UEFI {}.{}", major, minor / 10)?;
+			if minor % 10 != 0 {
+				write!(f, ".{}", minor % 10)?;
+			}
+		}
+		write!(f, "), {} configuration tables", self.configuraton_table.count)
+	}
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -173,3 +435,17 @@ pub struct ConfigurationTable
 
 
 
+
+#[cfg(test)]
+mod tests
+{
+	use super::Guid;
+
+	#[test]
+	fn guid_byte_order() {
+		// EFI System Partition type, as stored in a GPT entry
+		let bytes = [0x28, 0x73, 0x2A, 0xC1, 0x1F, 0xF8, 0xD2, 0x11, 0xBA, 0x4B, 0x00, 0xA0, 0xC9, 0x3E, 0xC9, 0x3B];
+		assert_eq!(Guid::from_bytes(&bytes), ::gpt::EFI_SYSTEM_PARTITION_GUID);
+		assert_eq!(::gpt::EFI_SYSTEM_PARTITION_GUID.to_bytes(), bytes);
+	}
+}
@@ -0,0 +1,84 @@
+//! Helpers for booting Linux through its EFI stub
+use core::ptr;
+use boot_services::BootServices;
+use boot_services::protocols::{self, DevicePath, LoadFile2};
+use {Status, Guid, Handle, Void};
+
+/// Vendor media GUID that the EFI stub looks up to find the initrd (`LINUX_EFI_INITRD_MEDIA_GUID`)
+pub const INITRD_MEDIA_GUID: Guid = Guid(0x5568e427,0x68fc,0x4f3d,[0xac,0x74,0xca,0x55,0x52,0x31,0xcc,0x68]);
+
+/// Device path the stub searches for: a single Vendor Media node followed by an End node
+///
+/// That is, `04 03 14 00` (type media, subtype vendor, length 20), the 16 bytes of `INITRD_MEDIA_GUID`,
+/// then `7F FF 04 00` (end of entire path).
+#[repr(C)]
+struct InitrdDevicePath
+{
+	vendor_hdr: [u8; 4],
+	vendor_guid: [u8; 16],
+	end: [u8; 4],
+}
+
+/// `LoadFile2` instance that serves an in-memory initrd to the Linux EFI stub
+///
+/// The stub (5.8+) locates a handle with `INITRD_MEDIA_GUID`'s device path, and calls `LoadFile2` on
+/// it to load the initrd into memory it allocates itself.
+#[repr(C)]
+pub struct InitrdLoader
+{
+	// NOTE: Must be the first field, the callback casts the protocol pointer back to the loader
+	proto: LoadFile2,
+	device_path: InitrdDevicePath,
+	data: &'static [u8],
+}
+impl InitrdLoader
+{
+	pub fn new(initrd: &'static [u8]) -> InitrdLoader {
+		InitrdLoader {
+			proto: LoadFile2 {
+				load_file: load_initrd,
+				},
+			device_path: InitrdDevicePath {
+				vendor_hdr: [0x04, 0x03, 20, 0],
+				vendor_guid: INITRD_MEDIA_GUID.to_bytes(),
+				end: [0x7F, 0xFF, 4, 0],
+				},
+			data: initrd,
+			}
+	}
+
+	/// Install the device path and `LoadFile2` protocol on a new handle, returning the handle
+	///
+	/// Both are installed in one `install_multiple` call, so a failure can't leave a handle with only the
+	/// device path (which the stub would find, and then fail to load from). Call before starting the
+	/// kernel image. It isn't uninstalled automatically.
+	///
+	/// UNSAFE: The loader must not be moved or dropped while installed (i.e. until the kernel has exited
+	/// boot services), as the firmware and the stub hold pointers into it
+	pub unsafe fn install(&self, bs: &BootServices) -> Result<Handle, Status> {
+		let dp = &self.device_path as *const InitrdDevicePath as *const Void;
+		let lf = &self.proto as *const LoadFile2 as *const Void;
+		bs.install_multiple(None, &[
+			(<DevicePath as protocols::Protocol>::guid(), dp),
+			(<LoadFile2 as protocols::Protocol>::guid(), lf),
+			])
+	}
+}
+
+extern "win64" fn load_initrd(this: &LoadFile2, _file_path: &DevicePath, boot_policy: bool, buffer_size: &mut usize, buffer: *mut Void) -> Status
+{
+	if boot_policy {
+		return ::status::UNSUPPORTED;
+	}
+	// SAFE: Only ever installed as the first field of an `InitrdLoader`
+	let loader = unsafe { &*(this as *const LoadFile2 as *const InitrdLoader) };
+	let len = loader.data.len();
+	if buffer.is_null() || *buffer_size < len {
+		*buffer_size = len;
+		return ::status::BUFFER_TOO_SMALL;
+	}
+	// SAFE: The caller provided a buffer of at least `len` bytes (checked above)
+	unsafe { ptr::copy_nonoverlapping(loader.data.as_ptr(), buffer as *mut u8, len); }
+	*buffer_size = len;
+	::status::SUCCESS
+}
@@ -0,0 +1,77 @@
+//! `log` crate backend (requires the `log` feature)
+//!
+//! Forwards records from crates that use the `log` facade to a UEFI text console, in the same format as
+//! the `log_*!` macros.
+//!
+//! ```no_run
+//! # fn f(st: &::uefi::SystemTable) {
+//! unsafe { ::uefi::logger::init(&st.con_out).ok(); }
+//! // ... `log::info!` etc. are now printed on the console
+//! ::uefi::logger::detach();	// Before `exit_boot_services`
+//! # }
+//! ```
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use log;
+use con::{SimpleTextOutputInterface, LogLevel, EfiLogger, log_at};
+
+/// Console that records are written to (null when detached)
+///
+/// NOTE: `log::set_logger` requires a `'static` logger, but the console is only valid while boot
+/// services are, so it's stored as a raw pointer (see the safety requirements on `init`).
+static OUTPUT: AtomicPtr<SimpleTextOutputInterface> = AtomicPtr::new(0 as *mut _);
+
+static LOGGER: EfiLog = EfiLog;
+
+struct EfiLog;
+impl log::Log for EfiLog
+{
+	fn enabled(&self, _metadata: &log::Metadata) -> bool {
+		!OUTPUT.load(Ordering::Relaxed).is_null()
+	}
+	fn log(&self, record: &log::Record) {
+		let p = OUTPUT.load(Ordering::Relaxed);
+		if p.is_null() {
+			return ;
+		}
+		// SAFE: Validity until `detach` is a precondition of `init`
+		let out = unsafe { &*p };
+		let level = match record.level()
+			{
+			log::Level::Error => LogLevel::Error,
+			log::Level::Warn => LogLevel::Warn,
+			log::Level::Info => LogLevel::Info,
+			// No colour for the verbose levels
+			l => {
+				use core::fmt::Write;
+				let mut logger = EfiLogger::new(out);
+				let _ = write!(&mut logger, "[{}] [{}] {}", l, record.target(), record.args());
+				return ;
+				},
+			};
+		log_at(out, level, record.target(), *record.args());
+	}
+	fn flush(&self) {
+	}
+}
+
+/// Install the console as the `log` crate's logger, with all levels enabled
+///
+/// Lower the level afterwards with `log::set_max_level` if needed. Returns an error if a logger has
+/// already been installed (by this or something else), in which case `out` isn't used.
+///
+/// UNSAFE: `out` must remain valid until `detach` is called - in particular, `detach` must be called
+/// before `exit_boot_services`, as the console is a boot services protocol.
+pub unsafe fn init(out: &SimpleTextOutputInterface) -> Result<(), log::SetLoggerError> {
+	log::set_logger(&LOGGER)?;
+	OUTPUT.store(out as *const _ as *mut _, Ordering::SeqCst);
+	log::set_max_level(log::LevelFilter::Trace);
+	Ok( () )
+}
+
+/// Stop writing records to the console (they're discarded from then on)
+///
+/// The `log` crate doesn't allow the logger to be replaced, so this just disconnects the output.
+pub fn detach() {
+	OUTPUT.store(ptr::null_mut(), Ordering::SeqCst);
+}
@@ -0,0 +1,111 @@
+//! Text-mode boot menu
+use core::fmt::{self, Write};
+use super::{SystemTable, SimpleTextOutputInterface, Color, scan_codes};
+use boot_services::{Event, TimerDelay, EVT_TIMER, TPL_APPLICATION};
+
+/// First screen row used by the menu (row 0 is left for a title)
+const FIRST_ROW: usize = 1;
+
+/// Show a menu of `entries` and wait for a selection, returning the chosen index (`None` on Esc)
+///
+/// Up/Down move the highlight (starting on the first entry), and Enter selects. With a non-zero
+/// `timeout_secs`, a countdown is shown and the highlighted entry (i.e. the first, unless the user has
+/// moved) is returned when it expires. Any key press stops the countdown. A zero timeout waits forever.
+///
+/// Entries are drawn one per row from row 1, with the countdown on the row after the last entry. Returns
+/// `None` if `entries` is empty, or if the console or timer can't be used.
+pub fn select(st: &SystemTable, entries: &[&str], timeout_secs: u32) -> Option<usize> {
+	if entries.is_empty() {
+		return None;
+	}
+	let bs = st.boot_services();
+	let out = st.con_out();
+	let timer = bs.create_event(EVT_TIMER, TPL_APPLICATION, None).ok()?;
+	// 1s = 10,000,000 100ns units
+	if bs.set_timer(&timer, TimerDelay::Periodic, 10_000_000).is_err() {
+		bs.close_event(timer);
+		return None;
+	}
+
+	let cursor_was_visible = out.mode.cursor_visible;
+	out.enable_cursor(false);
+	out.clear_screen();
+
+	let mut selected = 0;
+	let mut remaining = if timeout_secs > 0 { Some(timeout_secs) } else { None };
+	let rv = loop
+		{
+			draw(out, entries, selected, remaining);
+			let events = [Event(st.con_in().wait_for_key), timer];
+			match bs.wait_for_event(&events)
+			{
+			Ok(0) => {},
+			Ok(_) => {
+				// Timer tick
+				if let Some(r) = remaining {
+					if r <= 1 {
+						break Some(selected);
+					}
+					remaining = Some(r - 1);
+				}
+				continue ;
+				},
+			Err(_) => break None,
+			}
+
+			let key = match st.con_in().read_key_stroke()
+				{
+				Ok(k) => k,
+				Err(_) => continue,
+				};
+			remaining = None;
+			if key.is_enter() {
+				break Some(selected);
+			}
+			match key.scan_code
+			{
+			scan_codes::ESC => break None,
+			scan_codes::UP => if selected > 0 { selected -= 1; },
+			scan_codes::DOWN => if selected + 1 < entries.len() { selected += 1; },
+			scan_codes::HOME => selected = 0,
+			scan_codes::END => selected = entries.len() - 1,
+			_ => {},
+			}
+		};
+
+	let _ = bs.set_timer(&timer, TimerDelay::Cancel, 0);
+	bs.close_event(timer);
+	out.set_cursor_position(0, FIRST_ROW + entries.len() + 1);
+	out.enable_cursor(cursor_was_visible);
+	rv
+}
+
+fn draw(out: &SimpleTextOutputInterface, entries: &[&str], selected: usize, remaining: Option<u32>) {
+	for (i, entry) in entries.iter().enumerate()
+	{
+		out.set_cursor_position(0, FIRST_ROW + i);
+		if i == selected {
+			let _g = out.with_attribute(Color::Black, Color::LightGray);
+			let _ = write!(Text(out), " {} ", entry);
+		}
+		else {
+			let _ = write!(Text(out), " {} ", entry);
+		}
+	}
+	out.set_cursor_position(0, FIRST_ROW + entries.len());
+	match remaining
+	{
+	// Trailing spaces clear the end of a longer previous count
+	Some(r) => { let _ = write!(Text(out), "Booting in {}s (press any key to stop)  ", r); },
+	None => { let _ = write!(Text(out), "{:40}", ""); },
+	}
+}
+
+/// Unbuffered `fmt::Write` to a text output (unlike `EfiLogger`, doesn't add a newline)
+struct Text<'a>(&'a SimpleTextOutputInterface);
+impl<'a> fmt::Write for Text<'a>
+{
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.0.output_string_utf8(s).err_or( () ).map_err(|_| fmt::Error)
+	}
+}
@@ -0,0 +1,36 @@
+//! Panic reporting helpers
+//!
+//! These are building blocks for the binary's own `#[panic_handler]` (which this crate doesn't install)
+//!
+//! ```no_run
+//! # extern crate core;
+//! # static mut SYSTEM_TABLE: *const ::uefi::SystemTable = 0 as *const _;
+//! //#[panic_handler]
+//! fn panic(info: &::core::panic::PanicInfo) -> ! {
+//! 	let st = unsafe { &*SYSTEM_TABLE };
+//! 	::uefi::panic::write_panic(st.con_out(), info);
+//! 	::uefi::panic::halt()
+//! }
+//! ```
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use super::{EfiLogger, SimpleTextOutputInterface};
+
+/// Print the panic location and message to the console
+pub fn write_panic(out: &SimpleTextOutputInterface, info: &PanicInfo) {
+	let mut logger = EfiLogger::new(out);
+	let _ = match info.location()
+		{
+		Some(l) => write!(&mut logger, "PANIC at {}:{}:{}: ", l.file(), l.line(), l.column()),
+		None => write!(&mut logger, "PANIC: "),
+		};
+	// NOTE: Needs rustc 1.81+ (where `PanicMessage` is `Display`), like the rest of the crate's panic support
+	let _ = write!(&mut logger, "{}", info.message());
+}
+
+/// Halt forever (e.g. after reporting a panic, so the message stays on screen)
+///
+/// Use `RuntimeServices::reset_system` instead to reboot the machine.
+pub fn halt() -> ! {
+	loop {}
+}
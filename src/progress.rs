@@ -0,0 +1,110 @@
+//! Text-mode progress bar
+use super::{SimpleTextOutputInterface, Color, status};
+
+/// Single-line progress bar, drawn as `[####    ]  45%` on a fixed row
+///
+/// Occupies `width + 7` columns of one row, starting at column zero, and leaves the cursor where it was.
+/// If the console doesn't support cursor positioning (e.g. some serial terminals), it instead prints the
+/// percentage inline every 10%.
+pub struct ProgressBar<'a>
+{
+	out: &'a SimpleTextOutputInterface,
+	row: usize,
+	width: usize,
+	positioned: bool,
+	/// Last drawn percentage
+	last: Option<usize>,
+}
+impl<'a> ProgressBar<'a>
+{
+	/// Create (and draw) a bar of `width` cells on `row`
+	pub fn new(out: &'a SimpleTextOutputInterface, row: usize, width: usize) -> ProgressBar<'a> {
+		let (col, cur_row) = out.cursor_position();
+		let positioned = out.set_cursor_position(0, row) == status::SUCCESS;
+		if positioned {
+			out.set_cursor_position(col, cur_row);
+		}
+		let mut rv = ProgressBar {
+			out: out,
+			row: row,
+			width: width,
+			positioned: positioned,
+			last: None,
+			};
+		rv.draw(0);
+		rv
+	}
+
+	/// Update the bar to `fraction` (clamped to `0.0 ..= 1.0`)
+	pub fn set(&mut self, fraction: f32) {
+		let percent = if !(fraction > 0.0) { 0 } else if fraction >= 1.0 { 100 } else { (fraction * 100.0) as usize };
+		self.draw(percent);
+	}
+	/// Update the bar to `done` out of `total` (e.g. bytes read)
+	pub fn set_ratio(&mut self, done: u64, total: u64) {
+		let percent = if total == 0 || done >= total { 100 } else { (done * 100 / total) as usize };
+		self.draw(percent);
+	}
+
+	/// Draw the bar at 100%
+	pub fn finish(mut self) {
+		self.draw(100);
+		if !self.positioned {
+			self.out.output_string_utf8("\r\n");
+		}
+	}
+
+	fn draw(&mut self, percent: usize) {
+		if self.last == Some(percent) {
+			return ;
+		}
+		if !self.positioned {
+			// Fallback: print every 10%
+			let step = percent / 10;
+			if self.last.map(|l| l / 10) != Some(step) {
+				self.print_percent(percent);
+				self.out.output_string_utf8(" ");
+				self.last = Some(percent);
+			}
+			return ;
+		}
+		self.last = Some(percent);
+
+		let (col, row) = self.out.cursor_position();
+		self.out.set_cursor_position(0, self.row);
+		let filled = self.width * percent / 100;
+		self.out.output_string_utf8("[");
+		{
+			let _g = self.out.with_attribute(Color::LightGreen, Color::Black);
+			for _ in 0 .. filled {
+				self.out.output_string_utf8("#");
+			}
+		}
+		for _ in filled .. self.width {
+			self.out.output_string_utf8(" ");
+		}
+		self.out.output_string_utf8("] ");
+		if percent < 100 { self.out.output_string_utf8(" "); }
+		if percent < 10 { self.out.output_string_utf8(" "); }
+		self.print_percent(percent);
+		self.out.set_cursor_position(col, row);
+	}
+
+	fn print_percent(&self, percent: usize) {
+		// At most "100%"
+		let mut buf = [b'%'; 4];
+		let mut pos = 3;
+		let mut v = percent;
+		loop
+		{
+			pos -= 1;
+			buf[pos] = b'0' + (v % 10) as u8;
+			v /= 10;
+			if v == 0 || pos == 0 {
+				break;
+			}
+		}
+		// SAFE: Only ASCII digits and '%'
+		self.out.output_string_utf8(unsafe { ::core::str::from_utf8_unchecked(&buf[pos..]) });
+	}
+}
@@ -18,7 +18,8 @@ pub struct RuntimeServices
 	pub set_time: efi_fcn!{ fn(&Time) -> Status },
 
 	pub get_wakeup_time: efi_fcn!{ fn(&mut bool, &mut bool, &mut Time) -> Status },
-	pub set_wakeup_time: efi_fcn!{ fn(bool, &Time) -> Status },
+	/// `time` may be null when disabling the alarm
+	pub set_wakeup_time: efi_fcn!{ fn(bool, Option<&Time>) -> Status },
 
 	/// Pointer will be invalid (still physical) after being called
 	pub set_virtual_address_map: efi_fcn!{ fn(map_size: usize, descriptor_size: usize, descriptor_version: u32, virtual_map: *const super::boot_services::MemoryDescriptor) -> Status },
@@ -34,9 +35,16 @@ pub struct RuntimeServices
 	pub reset_system: efi_fcn!{ fn(ty: ResetType, sys_status: Status, data_size: usize, reset_data: *const u16) -> Status },
 
 	pub update_capsule: efi_fcn!{ fn(*const *const CapsuleHeader, usize, PhysicalAddress) -> Status },
-	pub query_capsure_capabilities: efi_fcn!{ fn(*const *const CapsuleHeader, usize, &mut u64, &mut ResetType) -> Status },
+	/// The reset type is written as a raw `EFI_RESET_TYPE` (it may be a value `ResetType` doesn't cover)
+	pub query_capsure_capabilities: efi_fcn!{ fn(*const *const CapsuleHeader, usize, &mut u64, /*reset_type:*/ &mut u32) -> Status },
 	pub query_variable_info: efi_fcn!{ fn(unk: u32, max_variable_storage_size: &mut u64, remaining_variable_storage_size: &mut u64, maximum_variable_size: &mut u64) -> Status },
 }
+// The table is 14 function pointers after the 24-byte header (with the last three being UEFI 2.0+, but the
+// slots are always present), in spec order. This fails to compile if a field is missing or added, and the
+// offsets of individual fields are checked in `tests::runtime_services_layout`.
+#[cfg(target_pointer_width="64")]
+#[allow(dead_code)]
+const RUNTIME_SERVICES_SIZE_CHECK: [(); 0x88] = [(); mem::size_of::<RuntimeServices>()];
 
 impl RuntimeServices
 {
@@ -56,6 +64,168 @@ impl RuntimeServices
 		(self.set_virtual_address_map)(map.len(), mem::size_of_val(&map[0]), 1, map.as_ptr())?;
 		Ok(self.make_handle())
 	}
+
+	/// Convert a physical pointer into runtime memory to its new virtual address
+	///
+	/// Only valid during the `SetVirtualAddressMap` flow - i.e. from an `EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE`
+	/// notification, before the call returns. Whether (and how) it fails when called at other times
+	/// depends on the firmware. Pass `OPTIONAL_POINTER` in `debug_disposition` to allow a null `addr`.
+	pub fn convert_pointer(&self, debug_disposition: usize, addr: &mut *const Void) -> Result<(), Status> {
+		// SAFE: Only the passed pointer is updated
+		unsafe { (self.convert_pointer)(debug_disposition, addr) }
+			.err_or( () )
+	}
+
+	/// Get the wakeup alarm, as `(enabled, pending, time)`
+	///
+	/// `pending` is set if the alarm has fired (and not been cleared by `set_wakeup`). Returns `UNSUPPORTED`
+	/// on platforms without a wakeup alarm.
+	pub fn get_wakeup(&self) -> Result<(bool, bool, Time), Status> {
+		let mut time = Time::default();
+		let mut enabled = false;
+		let mut pending = false;
+		// SAFE: No memory unsafety, all pointers are to valid locals
+		unsafe { (self.get_wakeup_time)(&mut enabled, &mut pending, &mut time) }?;
+		Ok( (enabled, pending, time) )
+	}
+	/// Set or disable the wakeup alarm
+	///
+	/// With `enable` set, the system wakes (or powers on, if supported) at `time`, which is required
+	/// (`INVALID_PARAMETER` if it's `None`). With `enable` clear, the alarm is disabled and `time` is ignored.
+	/// Returns `UNSUPPORTED` on platforms without a wakeup alarm.
+	pub fn set_wakeup(&self, enable: bool, time: Option<&Time>) -> Result<(), Status> {
+		let time = if enable {
+				Some( time.ok_or(::status::INVALID_PARAMETER)? )
+			}
+			else {
+				None
+			};
+		// SAFE: No memory unsafety, the time is only read
+		unsafe { (self.set_wakeup_time)(enable, time) }
+			.err_or( () )
+	}
+
+	/// Query the available variable storage for variables with the specified attributes
+	///
+	/// UEFI 2.0+, returns `UNSUPPORTED` on older firmware
+	pub fn query_variable_info(&self, attributes: u32) -> Result<VariableInfo,Status> {
+		if self.hdr.revision < ::REVISION_2_00 {
+			return Err(::status::UNSUPPORTED);
+		}
+		let mut rv = VariableInfo {
+			maximum_variable_storage_size: 0,
+			remaining_variable_storage_size: 0,
+			maximum_variable_size: 0,
+			};
+		// SAFE: No memory unsafety, all pointers are to valid locals
+		unsafe {
+			(self.query_variable_info)(attributes, &mut rv.maximum_variable_storage_size, &mut rv.remaining_variable_storage_size, &mut rv.maximum_variable_size)?; 
+		}
+		Ok(rv)
+	}
+
+	/// Reset the system (this does not return)
+	///
+	/// `sys_status` is the reason for the reset, and is reported to the firmware
+	pub fn reset_system(&self, ty: ResetType, sys_status: Status) -> ! {
+		// SAFE: No reset data passed
+		unsafe { (self.reset_system)(ty, sys_status, 0, ::core::ptr::null()); }
+		// The firmware shouldn't return, but just in case
+		loop {}
+	}
+
+	/// Obtain the next high 32 bits of the platform's monotonic counter
+	///
+	/// NOTE: This increments a counter in platform non-volatile storage, so shouldn't be called in a tight loop.
+	/// Combine with `BootServices::get_next_monotonic_count` for a 64-bit value that survives reboots.
+	pub fn next_high_monotonic_count(&self) -> Result<u32,Status> {
+		let mut v = 0;
+		// SAFE: No memory unsafety
+		unsafe { (self.get_next_high_monotonic_count)(&mut v) }?;
+		Ok(v)
+	}
+
+	/// Query if a set of capsules can be passed to `update_capsule`
+	///
+	/// Returns the maximum supported capsule size, and the type of reset required to process the capsules.
+	/// UEFI 2.0+, returns `UNSUPPORTED` on older firmware, and `DEVICE_ERROR` if the firmware reports an
+	/// unknown reset type.
+	pub fn query_capsule_capabilities(&self, headers: &[&CapsuleHeader]) -> Result<(u64,ResetType), Status> {
+		if self.hdr.revision < ::REVISION_2_00 {
+			return Err(::status::UNSUPPORTED);
+		}
+		let mut rt = 0;
+		let mut max_size = 0;
+		// SAFE: Array of valid references, and the capsules aren't processed
+		unsafe { (self.query_capsure_capabilities)(headers.as_ptr() as *const _, headers.len(), &mut max_size, &mut rt)?; }
+		let rt = match rt
+			{
+			0 => ResetType::Cold,
+			1 => ResetType::Warm,
+			2 => ResetType::Shutdown,
+			3 => ResetType::PlatformSpecific,
+			_ => return Err(::status::DEVICE_ERROR),
+			};
+		Ok( (max_size, rt) )
+	}
+
+	/// Pass capsules (e.g. firmware updates) to the firmware
+	///
+	/// `scatter_gather_list` is the physical address of an array of `CapsuleBlockDescriptor`s describing
+	/// where the capsule data is located in physical memory. A descriptor with a zero `length` and non-zero
+	/// `address` continues the list at that address, and a zero/zero descriptor terminates it. It may be
+	/// 0 if no capsule has `CAPSULE_FLAGS_PERSIST_ACROSS_RESET` set.
+	///
+	/// NOTE: Most capsules are only processed after a reset (see `query_capsule_capabilities` for the reset type),
+	/// which the caller is responsible for triggering. UEFI 2.0+, returns `UNSUPPORTED` on older firmware
+	///
+	/// UNSAFE: The scatter-gather list must be valid, and the capsule data must remain in place until the reset
+	pub unsafe fn update_capsule(&self, headers: &[&CapsuleHeader], scatter_gather_list: PhysicalAddress) -> Result<(), Status> {
+		if self.hdr.revision < ::REVISION_2_00 {
+			return Err(::status::UNSUPPORTED);
+		}
+		(self.update_capsule)(headers.as_ptr() as *const _, headers.len(), scatter_gather_list).err_or( () )
+	}
+}
+
+/// `convert_pointer` disposition: the pointer may be null (and is left as null)
+pub const OPTIONAL_POINTER: usize = 0x1;
+
+/// What remains of the system table once boot services have exited
+///
+/// Returned by `SystemTable::exit_boot_services`, as proof that the exit happened. Only the runtime
+/// services and the configuration tables are reachable through it - the console, protocols, and
+/// `BootServices` are boot-time only and aren't. Every `RuntimeServices` method (via `Deref`) is callable
+/// both before and after the exit.
+pub struct Runtime<'a>
+{
+	services: &'a RuntimeServices,
+	configuration_table: &'a [::ConfigurationTable],
+}
+impl<'a> Runtime<'a>
+{
+	/// UNSAFE: Boot services must have been exited, and `services` be the firmware's runtime services table
+	pub unsafe fn new(services: &'a RuntimeServices, configuration_table: &'a [::ConfigurationTable]) -> Runtime<'a> {
+		Runtime {
+			services: services,
+			configuration_table: configuration_table,
+			}
+	}
+
+	pub fn runtime_services(&self) -> &'a RuntimeServices {
+		self.services
+	}
+	/// Configuration tables (ACPI, SMBIOS, ...) - these stay in place after the exit
+	pub fn configuration_table(&self) -> &'a [::ConfigurationTable] {
+		self.configuration_table
+	}
+}
+impl<'a> ::core::ops::Deref for Runtime<'a>
+{
+	type Target = RuntimeServices;
+	fn deref(&self) -> &RuntimeServices {
+		self.services
+	}
 }
 
 pub struct RuntimeServicesHandle<'a>
@@ -81,6 +251,9 @@ pub enum ResetType
 	Cold,
 	Warm,
 	Shutdown,
+	/// Platform-specific reset, identified by a GUID at the start of the reset data (`reset_system`
+	/// doesn't pass any, so which reset happens is up to the firmware)
+	PlatformSpecific,
 }
 
 pub struct RuntimeServicesTime<'a>(&'a RuntimeServices);
@@ -116,6 +289,10 @@ impl<'a> RuntimeServicesTime<'a>
 		unsafe { (self.0.get_wakeup_time)(&mut enabled, &mut pending, &mut time) }?;
 		Ok( (pending, if enabled { Some(time) } else { None }) )
 	}
+	/// Set (`Some`) or disable (`None`) the wakeup alarm, see `RuntimeServices::set_wakeup`
+	pub fn set_wakeup_time(&mut self, time: Option<&Time>) -> Result<(),Status> {
+		self.0.set_wakeup(time.is_some(), time)
+	}
 }
 
 #[repr(C)]
@@ -129,7 +306,7 @@ pub struct Time
 	pub second: u8,
 	_pad: u8,
 	pub nanosecond: u32,
-	pub time_zone: u16,	// -1440 to 1440 or 2047
+	pub time_zone: u16,	// -1440 to 1440 or 2047 (`UNSPECIFIED_TIMEZONE`)
 	pub daylight: u8,
 	_pad2: u8,
 }
@@ -152,6 +329,160 @@ impl Default for Time {
     }
 }
 
+/// `Time::time_zone` value indicating that the time is local time with no known offset
+pub const UNSPECIFIED_TIMEZONE: u16 = 0x07FF;
+
+impl Time
+{
+	/// Offset from UTC in minutes (`localtime = UTC + offset`), or `None` if the timezone is unspecified
+	pub fn time_zone_offset(&self) -> Option<i16> {
+		if self.time_zone == UNSPECIFIED_TIMEZONE {
+			None
+		}
+		else {
+			Some(self.time_zone as i16)
+		}
+	}
+
+	/// Convert to seconds since the Unix epoch (1970-01-01T00:00:00Z)
+	///
+	/// If the timezone is specified it's used to convert to UTC, otherwise the time is treated as UTC.
+	/// Returns `None` if any of the fields are out of range.
+	pub fn to_unix(&self) -> Option<i64> {
+		if self.month < 1 || self.month > 12 {
+			return None;
+		}
+		if self.day < 1 || self.day > days_in_month(self.year as i64, self.month) {
+			return None;
+		}
+		if self.hour > 23 || self.minute > 59 || self.second > 59 {
+			return None;
+		}
+		let days = days_from_civil(self.year as i64, self.month, self.day);
+		let local = days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+		match self.time_zone_offset()
+		{
+		Some(ofs) if ofs < -1440 || ofs > 1440 => None,
+		Some(ofs) => Some(local - ofs as i64 * 60),
+		None => Some(local),
+		}
+	}
+
+	/// Construct a UTC time from seconds since the Unix epoch (suitable for `set_time`)
+	///
+	/// Returns `None` if the year is outside the range `EFI_TIME` allows (1900 to 9999)
+	pub fn from_unix(secs: i64) -> Option<Time> {
+		let mut days = secs / 86400;
+		let mut rem = secs % 86400;
+		if rem < 0 {
+			days -= 1;
+			rem += 86400;
+		}
+		let (year, month, day) = civil_from_days(days);
+		if year < 1900 || year > 9999 {
+			return None;
+		}
+		Some(Time {
+			year: year as u16,
+			month: month,
+			day: day,
+			hour: (rem / 3600) as u8,
+			minute: (rem / 60 % 60) as u8,
+			second: (rem % 60) as u8,
+			time_zone: 0,
+			.. Default::default()
+			})
+	}
+}
+
+/// ISO-8601 date and time (`YYYY-MM-DDTHH:MM:SS`)
+impl ::core::fmt::Display for Time
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+			self.year, self.month, self.day,
+			self.hour, self.minute, self.second
+			)
+	}
+}
+/// ISO-8601 with nanoseconds and the UTC offset (omitted if unspecified)
+impl ::core::fmt::Debug for Time
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		write!(f, "{}.{:09}", self, self.nanosecond)?;
+		if let Some(ofs) = self.time_zone_offset() {
+			let (sign, ofs) = if ofs < 0 { ('-', -(ofs as i32)) } else { ('+', ofs as i32) };
+			write!(f, "{}{:02}:{:02}", sign, ofs / 60, ofs % 60)?;
+		}
+		Ok( () )
+	}
+}
+
+/// Chronological ordering
+///
+/// Times are ordered by their instant (`to_unix`, which treats an unspecified timezone as UTC), then by
+/// nanosecond. Equal instants written in different timezones (and invalid times, which sort first) are
+/// then ordered field-by-field and by timezone, so this is a total order - but `==` means "same fields",
+/// not just "same instant".
+impl Ord for Time
+{
+	fn cmp(&self, other: &Time) -> ::core::cmp::Ordering {
+		let a = (self.to_unix(), self.nanosecond, (self.year, self.month, self.day, self.hour, self.minute, self.second), self.time_zone);
+		let b = (other.to_unix(), other.nanosecond, (other.year, other.month, other.day, other.hour, other.minute, other.second), other.time_zone);
+		a.cmp(&b)
+	}
+}
+impl PartialOrd for Time
+{
+	fn partial_cmp(&self, other: &Time) -> Option<::core::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl PartialEq for Time
+{
+	fn eq(&self, other: &Time) -> bool {
+		self.cmp(other) == ::core::cmp::Ordering::Equal
+	}
+}
+impl Eq for Time
+{
+}
+
+fn is_leap_year(year: i64) -> bool {
+	(year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+fn days_in_month(year: i64, month: u8) -> u8 {
+	match month
+	{
+	2 => if is_leap_year(year) { 29 } else { 28 },
+	4 | 6 | 9 | 11 => 30,
+	_ => 31,
+	}
+}
+/// Number of days between 1970-01-01 and the given date (proleptic Gregorian calendar)
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+	// Shift to a March-based year, so the leap day is at the end
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = if year >= 0 { year } else { year - 399 } / 400;
+	let year_of_era = year - era * 400;
+	let month_idx = (month as i64 + 9) % 12;
+	let day_of_year = (153 * month_idx + 2) / 5 + day as i64 - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
+/// Inverse of `days_from_civil`, returns (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+	let days = days + 719468;
+	let era = if days >= 0 { days } else { days - 146096 } / 146097;
+	let day_of_era = days - era * 146097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_idx = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * month_idx + 2) / 5 + 1) as u8;
+	let month = if month_idx < 10 { month_idx + 3 } else { month_idx - 9 } as u8;
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	(year, month, day)
+}
 #[repr(C)]
 pub struct TimeCapabilities
 {
@@ -202,15 +533,7 @@ impl<'a> RuntimeServicesStorage<'a>
 	}
 	//pub query_variable_info: efi_fcn!{ fn(unk: u32, max_variable_storage_size: &mut u64, remaining_variable_storage_size: &mut u64, maximum_variable_size: &mut u64) -> Status },
 	pub fn query_variable_info(&mut self, attr_mask: VariableAttributes) -> Result<VariableInfo,Status> {
-		let mut rv = VariableInfo {
-			maximum_variable_storage_size: 0,
-			remaining_variable_storage_size: 0,
-			maximum_variable_size: 0,
-			};
-		unsafe {
-			(self.0.query_variable_info)(attr_mask.0, &mut rv.maximum_variable_storage_size, &mut rv.remaining_variable_storage_size, &mut rv.maximum_variable_size)?; 
-		}
-		Ok(rv)
+		self.0.query_variable_info(attr_mask.0)
 	}
 	//pub set_variable: efi_fcn!{ fn(CStr16Ptr, &Guid, u32, usize, *const Void) -> Status },
 	pub fn set_variable(&mut self, name: &CStr16, guid: &Guid, attrs: VariableAttributes, data: &[u8]) -> Status {
@@ -222,22 +545,20 @@ impl<'a> RuntimeServicesStorage<'a>
 
 	//pub get_next_high_monotonic_count: efi_fcn!{ fn(&mut u32) -> Status },
 	pub fn get_next_high_monotonic_count(&mut self) -> Result<u32,Status> {
-		let mut v = 0;
-		// SAFE: No memory unsafety
-		unsafe { (self.0.get_next_high_monotonic_count)(&mut v) }?;
-		Ok(v)
+		self.0.next_high_monotonic_count()
 	}
 
 	//pub update_capsule: efi_fcn!{ fn(*const *const CapsuleHeader, usize, PhysicalAddress) -> Status },
 	pub unsafe fn update_capsule(&mut self, capsule_headers: &[&CapsuleHeader]) -> Status {
-		(self.0.update_capsule)(capsule_headers.as_ptr() as *const _, capsule_headers.len(), 0)
+		match self.0.update_capsule(capsule_headers, 0)
+		{
+		Ok( () ) => ::status::SUCCESS,
+		Err(e) => e,
+		}
 	}
-	//pub query_capsure_capabilities: efi_fcn!{ fn(*const *const CapsuleHeader, usize, &mut u64, &mut ResetType) -> Status },
+	//pub query_capsure_capabilities: efi_fcn!{ fn(*const *const CapsuleHeader, usize, &mut u64, &mut u32) -> Status },
 	pub unsafe fn query_capsure_capabilities(&mut self, capsule_headers: &[&CapsuleHeader]) -> Result<(u64,ResetType), Status> {
-		let mut rt = ResetType::Warm;
-		let mut max_size = 0;
-		(self.0.query_capsure_capabilities)(capsule_headers.as_ptr() as *const _, capsule_headers.len(), &mut max_size, &mut rt)?;
-		Ok( (max_size, rt) )
+		self.0.query_capsule_capabilities(capsule_headers)
 	}
 }
 
@@ -250,6 +571,23 @@ pub struct CapsuleHeader
 	pub capsule_image_size: u32,
 }
 
+/// Capsule must be kept in memory across a system reset (requires a scatter-gather list)
+pub const CAPSULE_FLAGS_PERSIST_ACROSS_RESET: u32 = 0x0001_0000;
+/// Capsule is placed in the configuration table after the reset
+pub const CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE: u32 = 0x0002_0000;
+/// Firmware should reset the system itself once the capsule is accepted
+pub const CAPSULE_FLAGS_INITIATE_RESET: u32 = 0x0004_0000;
+
+/// Entry in the capsule scatter-gather list passed to `update_capsule`
+#[repr(C)]
+pub struct CapsuleBlockDescriptor
+{
+	/// Length of the data block in bytes, or 0 if `address` points to the next descriptor array
+	pub length: u64,
+	/// Physical address of the data block (or the continuation descriptor array)
+	pub address: PhysicalAddress,
+}
+
 pub struct VariableAttributes(u32);
 macro_rules! def_bits {
 	($($mask:expr => $set:ident,$unset:ident,$test:ident),*$(,)*) => {
@@ -295,3 +633,73 @@ pub struct VariableInfo
 	pub maximum_variable_size: u64,
 }
 
+#[cfg(test)]
+mod tests
+{
+	use super::{Time, UNSPECIFIED_TIMEZONE, days_from_civil, RuntimeServices};
+
+	fn time(hour: u8, minute: u8, time_zone: u16) -> Time {
+		Time { year: 2020, month: 6, day: 1, hour: hour, minute: minute, time_zone: time_zone, .. Default::default() }
+	}
+
+	/// Field offsets from the spec (the size check alone doesn't catch two swapped fields)
+	#[test]
+	#[cfg(target_pointer_width="64")]
+	fn runtime_services_layout() {
+		// SAFE: Every field is an integer or a function pointer (which only has to be non-null to be valid)
+		let rs: RuntimeServices = unsafe { ::core::mem::transmute([1usize; 0x88 / 8]) };
+		let base = &rs as *const _ as usize;
+		assert_eq!(&rs.get_time as *const _ as usize - base, 0x18);
+		assert_eq!(&rs.set_wakeup_time as *const _ as usize - base, 0x30);
+		assert_eq!(&rs.set_virtual_address_map as *const _ as usize - base, 0x38);
+		assert_eq!(&rs.get_variable as *const _ as usize - base, 0x48);
+		assert_eq!(&rs.set_variable as *const _ as usize - base, 0x58);
+		assert_eq!(&rs.reset_system as *const _ as usize - base, 0x68);
+		assert_eq!(&rs.update_capsule as *const _ as usize - base, 0x70);
+		assert_eq!(&rs.query_variable_info as *const _ as usize - base, 0x80);
+	}
+
+	#[test]
+	fn unix_known_values() {
+		assert_eq!(days_from_civil(1970, 1, 1), 0);
+		assert_eq!(days_from_civil(2000, 3, 1), 11017);
+		let t = Time { year: 2000, month: 3, day: 1, hour: 12, minute: 34, second: 56, .. Default::default() };
+		assert_eq!(t.to_unix(), Some(951914096));
+		let t = Time::from_unix(951914096).unwrap();
+		assert_eq!((t.year, t.month, t.day, t.hour, t.minute, t.second), (2000, 3, 1, 12, 34, 56));
+	}
+
+	#[test]
+	fn from_unix_year_range() {
+		// 1900-01-01T00:00:00Z and 9999-12-31T23:59:59Z are the limits
+		assert!(Time::from_unix(-2208988800).is_some());
+		assert!(Time::from_unix(-2208988801).is_none());
+		assert!(Time::from_unix(253402300799).is_some());
+		assert!(Time::from_unix(253402300800).is_none());
+	}
+
+	#[test]
+	fn unix_round_trip_with_timezone() {
+		// 2021-12-31T23:30:00-05:00 is 2022-01-01T04:30:00Z
+		let t = Time { year: 2021, month: 12, day: 31, hour: 23, minute: 30, time_zone: -300i16 as u16, .. Default::default() };
+		let secs = t.to_unix().unwrap();
+		let utc = Time::from_unix(secs).unwrap();
+		assert_eq!((utc.year, utc.month, utc.day, utc.hour, utc.minute, utc.time_zone), (2022, 1, 1, 4, 30, 0));
+		assert_eq!(utc.to_unix(), Some(secs));
+	}
+
+	#[test]
+	fn sort_mixed_timezones() {
+		// 10:00+02:00 is 08:00 UTC, before 09:00Z and the zone-less 09:30 (treated as UTC)
+		let mut times = [time(9, 30, UNSPECIFIED_TIMEZONE), time(9, 0, 0), time(10, 0, 120)];
+		times.sort_unstable();
+		assert_eq!((times[0].hour, times[0].minute), (10, 0));
+		assert_eq!((times[1].hour, times[1].minute), (9, 0));
+		assert_eq!((times[2].hour, times[2].minute), (9, 30));
+		for a in times.iter() {
+			for b in times.iter() {
+				assert_eq!(a.cmp(b), b.cmp(a).reverse());
+			}
+		}
+	}
+}
@@ -0,0 +1,81 @@
+//! Raw 16550 UART output (x86_64 only)
+//!
+//! Doesn't need any firmware protocol, so it works before, during, and after boot services (on hardware
+//! or emulators that have a legacy COM port). Prefer the `SerialIo` protocol while boot services are
+//! available on machines without one.
+use core::fmt;
+
+/// I/O port base of the first legacy serial port
+pub const COM1: u16 = 0x3F8;
+
+// Register offsets (from the port base)
+const REG_DATA: u16 = 0;	// DLAB=1: divisor low byte
+const REG_INT_ENABLE: u16 = 1;	// DLAB=1: divisor high byte
+const REG_FIFO_CTRL: u16 = 2;
+const REG_LINE_CTRL: u16 = 3;
+const REG_MODEM_CTRL: u16 = 4;
+const REG_LINE_STATUS: u16 = 5;
+
+/// Line status: transmit holding register empty
+const LSR_THR_EMPTY: u8 = 0x20;
+
+/// Legacy serial port, driven with port I/O
+///
+/// Always configured as 115200 baud, 8 data bits, no parity, one stop bit (8N1), with interrupts off
+pub struct SerialPort
+{
+	base: u16,
+}
+impl SerialPort
+{
+	/// Initialise the UART at `base` (e.g. `COM1`)
+	///
+	/// UNSAFE: `base` must be the I/O port base of a 16550-compatible UART, and nothing else may be driving it
+	pub unsafe fn new(base: u16) -> SerialPort {
+		outb(base + REG_INT_ENABLE, 0x00);	// Disable interrupts
+		outb(base + REG_LINE_CTRL, 0x80);	// DLAB=1 to set the divisor
+		outb(base + REG_DATA, 0x01);	// Divisor 1 = 115200 baud
+		outb(base + REG_INT_ENABLE, 0x00);
+		outb(base + REG_LINE_CTRL, 0x03);	// DLAB=0, 8N1
+		outb(base + REG_FIFO_CTRL, 0xC7);	// Enable and clear FIFOs, 14-byte threshold
+		outb(base + REG_MODEM_CTRL, 0x03);	// DTR + RTS
+		SerialPort {
+			base: base,
+			}
+	}
+
+	/// Send a single byte, waiting for space in the transmit buffer
+	pub fn write_byte(&mut self, b: u8) {
+		// SAFE: Port validity is a precondition of `new`
+		unsafe {
+			while inb(self.base + REG_LINE_STATUS) & LSR_THR_EMPTY == 0 {
+			}
+			outb(self.base + REG_DATA, b);
+		}
+	}
+}
+impl fmt::Write for SerialPort
+{
+	/// Write a string, translating `\n` to `\r\n` for terminals
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		for b in s.bytes()
+		{
+			if b == b'\n' {
+				self.write_byte(b'\r');
+			}
+			self.write_byte(b);
+		}
+		Ok( () )
+	}
+}
+
+#[inline]
+unsafe fn outb(port: u16, val: u8) {
+	::core::arch::asm!("out dx, al", in("dx") port, in("al") val, options(nomem, nostack, preserves_flags));
+}
+#[inline]
+unsafe fn inb(port: u16) -> u8 {
+	let rv: u8;
+	::core::arch::asm!("in al, dx", out("al") rv, in("dx") port, options(nomem, nostack, preserves_flags));
+	rv
+}
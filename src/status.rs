@@ -1,15 +1,55 @@
 
 
+/// Error flag: the top bit of a `UINTN`, clear for success and warnings
+const ERROR_BIT: usize = 1 << (::core::mem::size_of::<usize>() * 8 - 1);
+
 #[repr(C)]
 #[derive(Copy,Clone,PartialEq,Eq)]
 /// EFI Status type
-pub struct Status(u64);
+///
+/// Same representation as `EFI_STATUS` (a `UINTN`), so it can be returned directly by firmware functions.
+/// Error codes have the top bit set, which is bit 63 on 64-bit targets and bit 31 on 32-bit (IA32) ones.
+pub struct Status(usize);
 impl Status
 {
 	#[inline]
-	pub fn new(val: u64) -> Status {
+	pub fn new(val: usize) -> Status {
+		Status(val)
+	}
+
+	/// Convert from the raw `EFI_STATUS` value used by the firmware ABI
+	#[inline]
+	pub fn from_usize(val: usize) -> Status {
 		Status(val)
 	}
+	/// Convert to the raw `EFI_STATUS` (`usize`) value, e.g. for returning from a custom protocol function
+	#[inline]
+	pub fn as_usize(self) -> usize {
+		self.0
+	}
+
+	/// Returns true for error codes (the top bit set)
+	#[inline]
+	pub fn is_error(&self) -> bool {
+		self.0 & ERROR_BIT != 0
+	}
+	/// Returns true for warnings (non-zero, but not an error) - the operation still completed
+	#[inline]
+	pub fn is_warning(&self) -> bool {
+		self.0 != 0 && !self.is_error()
+	}
+	/// Returns the warning code (e.g. 3 for `WARN_WRITE_FAILURE`), or `None` for success, errors, and
+	/// OEM-defined warnings too large for a `u16`
+	#[inline]
+	pub fn as_warning(&self) -> Option<u16> {
+		if self.is_warning() && self.0 <= 0xFFFF {
+			Some(self.0 as u16)
+		}
+		else {
+			None
+		}
+	}
+
 	#[inline]
 	pub fn err_or<T>(self, v: T) -> Result<T,Status> {
 		if self.0 == 0 {
@@ -84,6 +124,28 @@ impl ::core::ops::Try for Status
 	}
 }
 
+/// Formatting failed (e.g. the console rejected the output)
+impl From<::core::fmt::Error> for Status
+{
+	fn from(_: ::core::fmt::Error) -> Status {
+		DEVICE_ERROR
+	}
+}
+/// Byte data (e.g. a file or variable) wasn't valid UTF-8
+impl From<::core::str::Utf8Error> for Status
+{
+	fn from(_: ::core::str::Utf8Error) -> Status {
+		INVALID_PARAMETER
+	}
+}
+/// UTF-16 data contained an unpaired surrogate
+impl From<::core::char::DecodeUtf16Error> for Status
+{
+	fn from(_: ::core::char::DecodeUtf16Error) -> Status {
+		INVALID_PARAMETER
+	}
+}
+
 impl ::core::fmt::Debug for Status
 {
 	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -103,8 +165,8 @@ impl ::core::fmt::Debug for Status
 
 macro_rules! status_values {
 	($($v:expr => $n:ident $d:expr,)* @ERRORS $($v2:expr => $n2:ident $d2:expr,)*) => {
-		$(pub const $n : Status = Status(0 << 63 | $v);)*
-		$(pub const $n2 : Status = Status(1 << 63 | $v2);)*
+		$(pub const $n : Status = Status($v);)*
+		$(pub const $n2 : Status = Status(ERROR_BIT | $v2);)*
 
 		fn value_to_ident(v: Status)->Option<&'static str> {
 			match v
@@ -117,8 +179,8 @@ macro_rules! status_values {
 		fn value_to_description(v: Status) -> Option<&'static str> {
 			match v
 			{
-			$($n => Some(stringify!($d1)),)*
-			$($n2 => Some(stringify!($d2)),)*
+			$($n => Some($d),)*
+			$($n2 => Some($d2),)*
 			_ => None,
 			}
 		}
@@ -132,6 +194,7 @@ status_values! {
 	4 => WARN_BUFFER_TOO_SMALL "The resulting buffer was too small, and the data was truncated to the buffer size.",
 	5 => WARN_STALE_DATA "The data has not been updated within the timeframe set by local policy for this type of data.",
 	6 => WARN_FILE_SYSTEM "The resulting buffer contains UEFI-compliant file system.",
+	7 => WARN_RESET_REQUIRED "The operation will be processed across a system reset.",
 	@ERRORS
 	1 => LOAD_ERROR "The image failed to load.",
 	2 => INVALID_PARAMETER "A parameter was incorrect.",
@@ -152,5 +215,40 @@ status_values! {
 	17 => NO_MAPPING "A mapping to a device does not exist.",
 	18 => TIMEOUT "The timeout time expired.",
 	19 => NOT_STARTED "The protocol has not been started.",
+	20 => ALREADY_STARTED "The protocol has already been started.",
+	21 => ABORTED "The operation was aborted.",
+	22 => ICMP_ERROR "An ICMP error occurred during the network operation.",
+	23 => TFTP_ERROR "A TFTP error occurred during the network operation.",
+	24 => PROTOCOL_ERROR "A protocol error occurred during the network operation.",
+	25 => INCOMPATIBLE_VERSION "The function encountered an internal version that was incompatible with a version requested by the caller.",
+	26 => SECURITY_VIOLATION "The function was not performed due to a security violation.",
+	27 => CRC_ERROR "A CRC error was detected.",
+	28 => END_OF_MEDIA "Beginning or end of media was reached.",
+	31 => END_OF_FILE "The end of the file was reached.",
+	32 => INVALID_LANGUAGE "The language specified was invalid.",
+	33 => COMPROMISED_DATA "The security status of the data is unknown or compromised and the data must be updated or replaced to restore a valid security status.",
+	34 => IP_ADDRESS_CONFLICT "There is an address conflict address allocation.",
+	35 => HTTP_ERROR "A HTTP error occurred during the network operation.",
 }
 
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn error_bit_is_top_bit() {
+		assert_eq!(ERROR_BIT, !(!0usize >> 1));
+		assert!(Status::new(ERROR_BIT).is_error());
+		assert!(!Status::new(ERROR_BIT - 1).is_error());
+	}
+
+	#[test]
+	fn known_values() {
+		assert_eq!(WARN_RESET_REQUIRED.as_usize(), 7);
+		assert_eq!(CRC_ERROR.as_usize(), ERROR_BIT | 27);
+		assert_eq!(END_OF_FILE.as_usize(), ERROR_BIT | 31);
+		assert!(HTTP_ERROR == Status::new(ERROR_BIT | 35));
+	}
+}
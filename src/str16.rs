@@ -14,6 +14,28 @@ impl Str16
 		}
 	}
 
+	/// View a (possibly NUL-terminated) buffer as a string
+	///
+	/// The string ends at the first NUL, or at the end of the slice if there isn't one (e.g. a fixed-size
+	/// name field that's completely full). Unlike `from_slice`, NULs are never included.
+	pub fn from_units(units: &[u16]) -> &Str16 {
+		match units.iter().position(|&x| x == 0)
+		{
+		Some(l) => Self::from_slice(&units[..l]),
+		None => Self::from_slice(units),
+		}
+	}
+	/// View a NUL-terminated buffer as a string (excluding the NUL)
+	///
+	/// Returns `None` unless the last unit is NUL and it's the only NUL in the slice
+	pub fn from_units_with_nul(units: &[u16]) -> Option<&Str16> {
+		match units.iter().position(|&x| x == 0)
+		{
+		Some(l) if l == units.len() - 1 => Some(Self::from_slice(&units[..l])),
+		_ => None,
+		}
+	}
+
 	/// UNSAFE: Indexes input until NUL, lifetime inferred
 	#[inline]
 	pub unsafe fn from_nul_terminated<'a>(p: *const u16) -> &'a Str16 {
@@ -37,6 +59,25 @@ impl Str16
 	pub fn chars(&self) -> Chars {
 		Chars(&self.0)
 	}
+
+	/// Compare with a string, ignoring ASCII case (e.g. for matching names on FAT volumes)
+	///
+	/// Only `A-Z`/`a-z` are folded, other characters (including non-ASCII letters) must match exactly.
+	pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+		fn fold(u: u16) -> u16 {
+			if u >= b'A' as u16 && u <= b'Z' as u16 { u + 0x20 } else { u }
+		}
+		let mut units = self.0.iter();
+		for o in other.encode_utf16()
+		{
+			match units.next()
+			{
+			Some(&u) if fold(u) == fold(o) => {},
+			_ => return false,
+			}
+		}
+		units.next().is_none()
+	}
 }
 impl ::core::fmt::Display for Str16 {
 	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -99,15 +140,72 @@ impl<'a> Iterator for Chars<'a>
 	}
 }
 
+/// Number of UTF-16 code units needed to encode `s` (characters outside the BMP take two)
+///
+/// Doesn't include a NUL terminator, add one for that when sizing a buffer for a `CStr16`.
+pub fn utf16_len(s: &str) -> usize {
+	s.chars().map(|c| if c as u32 >= 0x1_0000 { 2 } else { 1 }).sum()
+}
+
 /// Pointer to a UCS-2 NUL-terminated string
 pub type CStr16Ptr = *const u16;
 
+/// Bounded conversion of a raw `CStr16Ptr` to a borrowed string
+pub trait CStr16PtrExt
+{
+	/// Scan at most `max_len` code units (including the NUL) for the terminator
+	///
+	/// Returns `None` if the pointer is null, or if no NUL is found within `max_len`. Bounding the scan
+	/// means a malformed (unterminated or garbage) pointer from firmware can't cause an unbounded read.
+	///
+	/// UNSAFE: The pointer must be valid for reads up to the NUL or `max_len` code units (whichever is
+	/// first), and the lifetime is inferred.
+	unsafe fn to_cstr16<'a>(self, max_len: usize) -> Option<&'a CStr16>;
+}
+impl CStr16PtrExt for CStr16Ptr
+{
+	unsafe fn to_cstr16<'a>(self, max_len: usize) -> Option<&'a CStr16> {
+		if self.is_null() {
+			return None;
+		}
+		let mut len = 0;
+		while len < max_len
+		{
+			if *self.offset(len as isize) == 0 {
+				let s = ::core::slice::from_raw_parts(self, len + 1);
+				return Some( CStr16::from_slice(s) );
+			}
+			len += 1;
+		}
+		None
+	}
+}
+
 /// Safe unsized UCS-2 NUL-terminated string type
 pub struct CStr16([u16]);
 impl CStr16 {
 	pub fn as_ptr(&self) -> CStr16Ptr {
 		self.0.as_ptr()
 	}
+	/// UNSAFE: Indexes input until NUL, lifetime inferred
+	pub unsafe fn from_nul_terminated<'a>(p: CStr16Ptr) -> &'a CStr16 {
+		let len = Str16::from_nul_terminated(p).0.len();
+		Self::from_slice( ::core::slice::from_raw_parts(p, len + 1) )
+	}
+	/// Borrow the string contents (without the NUL terminator)
+	pub fn as_str16(&self) -> &Str16 {
+		Str16::from_slice(&self.0[.. self.0.len() - 1])
+	}
+	/// View a fixed-size name field (e.g. `FileInfo::FileName`) as a string, stopping at the first NUL or the
+	/// end of the field
+	///
+	/// Use this rather than `from_slice` for firmware-filled arrays: a name that fills the whole array has
+	/// no terminator, which `from_slice` panics on (and a raw pointer scan would read past). Same as
+	/// `Str16::from_units`.
+	pub fn from_bounded_units(units: &[u16]) -> &Str16 {
+		Str16::from_units(units)
+	}
+	/// Panics if `s` doesn't contain a NUL (the string ends at the first one)
 	pub fn from_slice(s: &[u16]) -> &CStr16 {
 		let l = s.iter().position(|&x| x == 0).expect("No NUL in slice passed to CStr16::from_slice");
 		let ss = &s[..l+1];
@@ -126,7 +224,7 @@ impl<'a> From<&'a [u16]> for &'a CStr16
 impl ::core::fmt::Display for CStr16
 {
 	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-		Str16::from_slice(&self.0[.. self.0.len() - 1]).fmt(f)
+		self.as_str16().fmt(f)
 	}
 }
 
@@ -146,3 +244,123 @@ impl<'h> ::borrow::Borrow<CStr16> for CString16<'h>
 	}
 }
 
+
+/// Fixed-capacity NUL-terminated UCS-2 string, for building paths without a heap
+///
+/// The capacity `N` includes the NUL terminator. Pushes that would overflow the buffer fail with
+/// `BUFFER_TOO_SMALL` and leave the string unchanged (they don't truncate).
+pub struct ArrayCStr16<const N: usize>
+{
+	buf: [u16; N],
+	len: usize,
+}
+impl<const N: usize> ArrayCStr16<N>
+{
+	/// Create a new empty string (panics if `N` is zero, as there's no room for the terminator)
+	pub fn new() -> Self {
+		assert!(N > 0, "ArrayCStr16 requires space for a NUL terminator");
+		ArrayCStr16 {
+			buf: [0; N],
+			len: 0,
+			}
+	}
+
+	/// Number of code units in the string (excluding the NUL terminator)
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	/// Remove all characters from the string
+	pub fn clear(&mut self) {
+		self.len = 0;
+		self.buf[0] = 0;
+	}
+
+	/// Append a single character (encoded as one or two code units)
+	///
+	/// Returns `INVALID_PARAMETER` for NUL, and `BUFFER_TOO_SMALL` if there's no space left
+	pub fn push_char(&mut self, c: char) -> Result<(), ::Status> {
+		if c == '\0' {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let mut tmp = [0; 2];
+		let units = c.encode_utf16(&mut tmp);
+		self.push_units(units)
+	}
+	/// Append a rust string
+	///
+	/// Returns `INVALID_PARAMETER` if the string contains NUL, and `BUFFER_TOO_SMALL` if it doesn't fit
+	pub fn push_str(&mut self, s: &str) -> Result<(), ::Status> {
+		if s.chars().any(|c| c == '\0') {
+			return Err(::status::INVALID_PARAMETER);
+		}
+		let count = utf16_len(s);
+		if self.len + count + 1 > N {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		for (d, s) in self.buf[self.len..].iter_mut().zip(s.encode_utf16()) {
+			*d = s;
+		}
+		self.len += count;
+		self.buf[self.len] = 0;
+		Ok( () )
+	}
+	fn push_units(&mut self, units: &[u16]) -> Result<(), ::Status> {
+		if self.len + units.len() + 1 > N {
+			return Err(::status::BUFFER_TOO_SMALL);
+		}
+		self.buf[self.len .. self.len + units.len()].copy_from_slice(units);
+		self.len += units.len();
+		self.buf[self.len] = 0;
+		Ok( () )
+	}
+
+	/// Borrow as a NUL-terminated string (e.g. for passing to `File::open`)
+	#[inline]
+	pub fn as_cstr16(&self) -> &CStr16 {
+		CStr16::from_slice(&self.buf[.. self.len + 1])
+	}
+	/// Borrow the string contents (without the terminator)
+	#[inline]
+	pub fn as_str16(&self) -> &Str16 {
+		Str16::from_slice(&self.buf[.. self.len])
+	}
+}
+impl<const N: usize> ::core::fmt::Display for ArrayCStr16<N>
+{
+	fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+		self.as_str16().fmt(f)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::Str16;
+
+	fn units(s: &str, buf: &mut [u16; 32]) -> usize {
+		let mut n = 0;
+		for (d, u) in buf.iter_mut().zip(s.encode_utf16()) {
+			*d = u;
+			n += 1;
+		}
+		n
+	}
+
+	#[test]
+	fn eq_ignore_ascii_case() {
+		let mut buf = [0; 32];
+		let n = units("BOOTX64.EFI", &mut buf);
+		let s = Str16::from_slice(&buf[..n]);
+		assert!(s.eq_ignore_ascii_case("bootx64.efi"));
+		assert!(s.eq_ignore_ascii_case("BootX64.Efi"));
+		assert!(!s.eq_ignore_ascii_case("bootx64.ef"));
+		assert!(!s.eq_ignore_ascii_case("bootx64.efi2"));
+
+		// Only ASCII is folded
+		let n = units("ÉCRAN.TXT", &mut buf);
+		let s = Str16::from_slice(&buf[..n]);
+		assert!(s.eq_ignore_ascii_case("Écran.txt"));
+		assert!(!s.eq_ignore_ascii_case("écran.txt"));
+	}
+}